@@ -0,0 +1,481 @@
+mod output;
+
+use std::path::{ Path, PathBuf };
+
+use anyhow::{ anyhow, Context as _, Result };
+use clap::{ Parser, Subcommand, ValueEnum };
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{ read_keypair_file, Keypair, Signer };
+use solana_sdk::transaction::Transaction;
+use anchor_spl::associated_token::get_associated_token_address;
+use swap::constants::PAUSE_ALL;
+use swap::state::PoolConfigAccount;
+use swap_client::{
+    decode_client_error,
+    fetch_pool_config,
+    fetch_pools,
+    fetch_program_config,
+    instructions,
+    PoolKeys,
+};
+
+#[derive(Parser)]
+#[command(name = "swap-cli", about = "Admin command line for the swap program")]
+struct Cli {
+    /// rpc endpoint
+    #[arg(long, short = 'u', global = true, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// fee payer and default signer, defaults to the solana cli keypair
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<PathBuf>,
+    /// print the transaction instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+    /// print machine readable output
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// create a pool owned by the --keypair signer
+    InitPool {
+        #[arg(long)]
+        mint: Pubkey,
+        /// base units bought for --price-denominator SOL
+        #[arg(long)]
+        price: u64,
+        #[arg(long, default_value_t = 1)]
+        price_denominator: u64,
+        #[arg(long)]
+        master_authority: Pubkey,
+        #[arg(long, default_value_t = 0)]
+        index: u64,
+    },
+    /// deposit tokens from the --keypair signer into the pool
+    AddLiquidity {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// defaults to the depositor's associated token account
+        #[arg(long)]
+        from: Option<Pubkey>,
+        #[arg(long)]
+        with_roles: bool,
+    },
+    /// buy tokens with lamports from the --keypair signer
+    Swap {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long)]
+        lamports: u64,
+        /// pool authority keypair, it co-signs every swap
+        #[arg(long)]
+        authority: PathBuf,
+    },
+    /// pause or resume every operation on the pool
+    SetActive {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(action = clap::ArgAction::Set)]
+        active: bool,
+        #[arg(long, default_value_t = 0)]
+        reason: u16,
+        #[arg(long)]
+        with_roles: bool,
+    },
+    WithdrawToken {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        #[command(flatten)]
+        withdraw: WithdrawArgs,
+    },
+    WithdrawNative {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        #[command(flatten)]
+        withdraw: WithdrawArgs,
+    },
+    /// withdraw the whole vault
+    Drain {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long, value_enum)]
+        kind: Kind,
+        #[command(flatten)]
+        withdraw: WithdrawArgs,
+    },
+    ShowPool {
+        #[arg(long)]
+        pool: Pubkey,
+    },
+    ListPools,
+}
+
+#[derive(clap::Args)]
+struct WithdrawArgs {
+    /// master authority keypair, it co-signs every withdrawal
+    #[arg(long)]
+    master_authority: PathBuf,
+    /// token account for token withdrawals, wallet for native ones; defaults to the authority
+    #[arg(long)]
+    destination: Option<Pubkey>,
+    /// multisig approver keypairs
+    #[arg(long = "approver")]
+    approvers: Vec<PathBuf>,
+    #[arg(long)]
+    with_roles: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Kind {
+    Token,
+    Native,
+}
+
+struct Context {
+    client: RpcClient,
+    payer: Keypair,
+    dry_run: bool,
+    json: bool,
+}
+
+fn read_keypair(path: &Path) -> Result<Keypair> {
+    read_keypair_file(path).map_err(|err| anyhow!("failed to read keypair {}: {}", path.display(), err))
+}
+
+fn default_keypair_path() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME is not set, pass --keypair")?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
+
+impl Context {
+    fn execute(&self, command: Command) -> Result<()> {
+        let payer = self.payer.pubkey();
+        match command {
+            Command::InitPool { mint, price, price_denominator, master_authority, index } => {
+                let program_config = fetch_program_config(&self.client)?;
+                let keys = PoolKeys::new(payer, mint, index);
+                let instruction = instructions::init_pool(
+                    &keys,
+                    &payer,
+                    &master_authority,
+                    program_config.pool_count,
+                    &program_config.protocol_fee_recipient,
+                    price,
+                    price_denominator
+                );
+                self.send("init-pool", &[instruction], &[])
+            }
+            Command::AddLiquidity { pool, amount, from, with_roles } => {
+                let (keys, config) = self.pool(&pool)?;
+                let from = from.unwrap_or_else(|| {
+                    get_associated_token_address(&payer, &keys.token_mint_address)
+                });
+                let instruction = instructions::add_liquid(
+                    &keys,
+                    &config.authority,
+                    &payer,
+                    &from,
+                    with_roles,
+                    amount
+                );
+                self.send("add-liquidity", &[instruction], &[])
+            }
+            Command::Swap { pool, lamports, authority } => {
+                let (keys, _) = self.pool(&pool)?;
+                let authority = read_keypair(&authority)?;
+                let instruction = instructions::swap_token(
+                    &keys,
+                    &authority.pubkey(),
+                    &payer,
+                    lamports
+                );
+                self.send("swap", &[instruction], &[&authority])
+            }
+            Command::SetActive { pool, active, reason, with_roles } => {
+                let (keys, _) = self.pool(&pool)?;
+                let instruction = instructions::set_pause(
+                    &keys,
+                    &payer,
+                    with_roles,
+                    PAUSE_ALL,
+                    !active,
+                    reason
+                );
+                self.send("set-active", &[instruction], &[])
+            }
+            Command::WithdrawToken { pool, amount, withdraw } =>
+                self.withdraw("withdraw-token", &pool, Kind::Token, Some(amount), &withdraw),
+            Command::WithdrawNative { pool, amount, withdraw } =>
+                self.withdraw("withdraw-native", &pool, Kind::Native, Some(amount), &withdraw),
+            Command::Drain { pool, kind, withdraw } =>
+                self.withdraw("drain", &pool, kind, None, &withdraw),
+            // handled in run without loading the payer
+            Command::ShowPool { .. } | Command::ListPools => unreachable!(),
+        }
+    }
+
+    fn pool(&self, address: &Pubkey) -> Result<(PoolKeys, PoolConfigAccount)> {
+        let config = fetch_pool_config(&self.client, address)?;
+        Ok((PoolKeys::from_config(*address, &config), config))
+    }
+
+    fn send(&self, command: &str, instructions: &[Instruction], signers: &[&Keypair]) -> Result<()> {
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&self.payer.pubkey()));
+        if self.dry_run {
+            output::print_transaction(command, &transaction, self.json);
+            return Ok(());
+        }
+
+        // the payer may also fill an authority slot, sign once per key
+        let mut keypairs: Vec<&Keypair> = vec![&self.payer];
+        for signer in signers {
+            if !keypairs.iter().any(|keypair| keypair.pubkey() == signer.pubkey()) {
+                keypairs.push(signer);
+            }
+        }
+        let blockhash = self.client.get_latest_blockhash()?;
+        transaction.try_sign(&keypairs, blockhash)?;
+        let signature = self.client
+            .send_and_confirm_transaction(&transaction)
+            .map_err(|err| match decode_client_error(&err) {
+                Some(program_error) => anyhow!(program_error),
+                None => err.into(),
+            })?;
+        output::print_signature(command, &signature, self.json);
+        Ok(())
+    }
+
+    fn withdraw(
+        &self,
+        command: &str,
+        pool: &Pubkey,
+        kind: Kind,
+        amount: Option<u64>,
+        args: &WithdrawArgs
+    ) -> Result<()> {
+        let (keys, _) = self.pool(pool)?;
+        let authority = self.payer.pubkey();
+        let master_authority = read_keypair(&args.master_authority)?;
+        let approvers = args.approvers
+            .iter()
+            .map(|path| read_keypair(path))
+            .collect::<Result<Vec<_>>>()?;
+        let approver_keys: Vec<Pubkey> = approvers
+            .iter()
+            .map(|approver| approver.pubkey())
+            .collect();
+
+        let instruction = match kind {
+            Kind::Token => {
+                let destination = args.destination.unwrap_or_else(|| {
+                    get_associated_token_address(&authority, &keys.token_mint_address)
+                });
+                match amount {
+                    Some(amount) =>
+                        instructions::withdraw_token(
+                            &keys,
+                            &authority,
+                            &master_authority.pubkey(),
+                            &destination,
+                            args.with_roles,
+                            &approver_keys,
+                            amount
+                        ),
+                    None =>
+                        instructions::drain_token(
+                            &keys,
+                            &authority,
+                            &master_authority.pubkey(),
+                            &destination,
+                            args.with_roles,
+                            &approver_keys
+                        ),
+                }
+            }
+            Kind::Native => {
+                let destination = args.destination.unwrap_or(authority);
+                match amount {
+                    Some(amount) =>
+                        instructions::withdraw_native(
+                            &keys,
+                            &authority,
+                            &master_authority.pubkey(),
+                            &destination,
+                            args.with_roles,
+                            &approver_keys,
+                            amount
+                        ),
+                    None =>
+                        instructions::drain_native(
+                            &keys,
+                            &authority,
+                            &master_authority.pubkey(),
+                            &destination,
+                            args.with_roles,
+                            &approver_keys
+                        ),
+                }
+            }
+        };
+
+        let mut signers = vec![&master_authority];
+        signers.extend(approvers.iter());
+        self.send(command, &[instruction], &signers)
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let client = RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed());
+    // reads need no keypair, only commands that build a transaction load the payer
+    match cli.command {
+        Command::ShowPool { pool } => {
+            let config = fetch_pool_config(&client, &pool)?;
+            output::print_pool(&pool, &config, cli.json);
+            Ok(())
+        }
+        Command::ListPools => {
+            let pools = fetch_pools(&client)?;
+            output::print_pools(&pools, cli.json);
+            Ok(())
+        }
+        command => {
+            let keypair_path = match cli.keypair {
+                Some(path) => path,
+                None => default_keypair_path()?,
+            };
+            let context = Context {
+                client,
+                payer: read_keypair(&keypair_path)?,
+                dry_run: cli.dry_run,
+                json: cli.json,
+            };
+            context.execute(command)
+        }
+    }
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {:#}", err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("swap-cli").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn reads_parse_without_a_keypair() {
+        let pool = Pubkey::new_unique();
+        let cli = parse(&["show-pool", "--pool", &pool.to_string()]);
+        assert!(cli.keypair.is_none());
+        assert!(!cli.json);
+        assert!(matches!(cli.command, Command::ShowPool { pool: parsed } if parsed == pool));
+
+        // global flags are accepted after the subcommand
+        let cli = parse(&["list-pools", "--json", "-u", "http://localhost:8899"]);
+        assert!(cli.json);
+        assert_eq!(cli.url, "http://localhost:8899");
+        assert!(matches!(cli.command, Command::ListPools));
+    }
+
+    #[test]
+    fn parses_init_pool_defaults() {
+        let mint = Pubkey::new_unique();
+        let master = Pubkey::new_unique();
+        let cli = parse(
+            &[
+                "init-pool",
+                "--mint",
+                &mint.to_string(),
+                "--price",
+                "10",
+                "--master-authority",
+                &master.to_string(),
+            ]
+        );
+        match cli.command {
+            Command::InitPool { mint: parsed, price, price_denominator, master_authority, index } => {
+                assert_eq!(parsed, mint);
+                assert_eq!(price, 10);
+                assert_eq!(price_denominator, 1);
+                assert_eq!(master_authority, master);
+                assert_eq!(index, 0);
+            }
+            _ => panic!("expected init-pool"),
+        }
+    }
+
+    #[test]
+    fn parses_transaction_commands() {
+        let pool = Pubkey::new_unique().to_string();
+        let cli = parse(&["set-active", "--pool", &pool, "false", "--reason", "3", "--dry-run"]);
+        assert!(cli.dry_run);
+        assert!(
+            matches!(cli.command, Command::SetActive { active: false, reason: 3, with_roles: false, .. })
+        );
+
+        let cli = parse(
+            &[
+                "-k",
+                "payer.json",
+                "drain",
+                "--pool",
+                &pool,
+                "--kind",
+                "native",
+                "--master-authority",
+                "master.json",
+                "--approver",
+                "a.json",
+                "--approver",
+                "b.json",
+            ]
+        );
+        assert_eq!(cli.keypair, Some(PathBuf::from("payer.json")));
+        match cli.command {
+            Command::Drain { kind: Kind::Native, withdraw, .. } => {
+                assert_eq!(withdraw.master_authority, PathBuf::from("master.json"));
+                assert_eq!(withdraw.approvers, vec![PathBuf::from("a.json"), PathBuf::from("b.json")]);
+                assert!(withdraw.destination.is_none());
+            }
+            _ => panic!("expected a native drain"),
+        }
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        let pool = Pubkey::new_unique().to_string();
+        let parse_err = |args: &[&str]| {
+            Cli::try_parse_from(std::iter::once("swap-cli").chain(args.iter().copied())).is_err()
+        };
+        assert!(parse_err(&["show-pool"]));
+        assert!(parse_err(&["show-pool", "--pool", "not-a-key"]));
+        assert!(parse_err(&["swap", "--pool", &pool, "--lamports", "-1", "--authority", "a.json"]));
+        assert!(parse_err(&["drain", "--pool", &pool, "--kind", "lamports", "--master-authority", "m.json"]));
+        assert!(parse_err(&["withdraw-token", "--pool", &pool, "--amount", "1"]));
+    }
+}
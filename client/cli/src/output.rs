@@ -0,0 +1,187 @@
+use serde_json::{ json, Value };
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+use swap::state::PoolConfigAccount;
+
+fn pool_json(address: &Pubkey, config: &PoolConfigAccount) -> Value {
+    json!({
+        "address": address.to_string(),
+        "token_mint_address": config.token_mint_address.to_string(),
+        "pool_index": config.pool_index,
+        "creator": config.creator.to_string(),
+        "authority": config.authority.to_string(),
+        "master_authority": config.master_authority.to_string(),
+        "guardian": config.guardian.to_string(),
+        "pool_token_account": config.pool_token_account.to_string(),
+        "pool_native_account": config.pool_native_account.to_string(),
+        "token_price": config.token_price,
+        "price_denominator": config.price_denominator(),
+        "fee_bps": config.fee_bps,
+        "pause_flags": config.pause_flags,
+        "pause_reason": config.pause_reason,
+        "is_verified": config.is_verified,
+        "version": config.version,
+        "swap_count": config.swap_count,
+        "total_lamports_in": config.total_lamports_in,
+        "total_tokens_out": config.total_tokens_out,
+        "unique_buyers": config.unique_buyers,
+        "total_liquidity_added": config.total_liquidity_added,
+        "last_swap_at": config.last_swap_at,
+    })
+}
+
+fn transaction_json(command: &str, transaction: &Transaction) -> Value {
+    let message = &transaction.message;
+    let instructions: Vec<Value> = message.instructions
+        .iter()
+        .map(|instruction| {
+            let accounts: Vec<Value> = instruction.accounts
+                .iter()
+                .map(|index| {
+                    let index = *index as usize;
+                    json!({
+                        "pubkey": message.account_keys[index].to_string(),
+                        "is_signer": message.is_signer(index),
+                        "is_writable": message.is_writable(index),
+                    })
+                })
+                .collect();
+            json!({
+                "program_id": message.account_keys[instruction.program_id_index as usize].to_string(),
+                "accounts": accounts,
+                "data": bs58::encode(&instruction.data).into_string(),
+            })
+        })
+        .collect();
+    json!({
+        "command": command,
+        "fee_payer": message.account_keys[0].to_string(),
+        "instructions": instructions,
+    })
+}
+
+pub fn print_transaction(command: &str, transaction: &Transaction, json: bool) {
+    let value = transaction_json(command, transaction);
+    if json {
+        println!("{}", value);
+        return;
+    }
+    println!("{} (dry run)", command);
+    println!("fee payer: {}", value["fee_payer"].as_str().unwrap_or_default());
+    for instruction in value["instructions"].as_array().into_iter().flatten() {
+        println!("program: {}", instruction["program_id"].as_str().unwrap_or_default());
+        for account in instruction["accounts"].as_array().into_iter().flatten() {
+            println!(
+                "  {} signer={} writable={}",
+                account["pubkey"].as_str().unwrap_or_default(),
+                account["is_signer"],
+                account["is_writable"]
+            );
+        }
+        println!("  data: {}", instruction["data"].as_str().unwrap_or_default());
+    }
+}
+
+pub fn print_signature(command: &str, signature: &Signature, json: bool) {
+    if json {
+        println!("{}", json!({ "command": command, "signature": signature.to_string() }));
+    } else {
+        println!("{}: {}", command, signature);
+    }
+}
+
+pub fn print_pool(address: &Pubkey, config: &PoolConfigAccount, json: bool) {
+    let value = pool_json(address, config);
+    if json {
+        println!("{}", value);
+        return;
+    }
+    for (key, field) in value.as_object().into_iter().flatten() {
+        match field.as_str() {
+            Some(text) => println!("{}: {}", key, text),
+            None => println!("{}: {}", key, field),
+        }
+    }
+}
+
+pub fn print_pools(pools: &[(Pubkey, PoolConfigAccount)], json: bool) {
+    if json {
+        let values: Vec<Value> = pools
+            .iter()
+            .map(|(address, config)| pool_json(address, config))
+            .collect();
+        println!("{}", Value::Array(values));
+        return;
+    }
+    for (address, config) in pools {
+        println!(
+            "{} mint={} index={} price={}/{} paused={:#05b} verified={}",
+            address,
+            config.token_mint_address,
+            config.pool_index,
+            config.token_price,
+            config.price_denominator(),
+            config.pause_flags,
+            config.is_verified
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountDeserialize;
+    use solana_sdk::instruction::{ AccountMeta, Instruction };
+
+    fn config() -> PoolConfigAccount {
+        let data = vec![0u8; PoolConfigAccount::LEN];
+        let mut config = PoolConfigAccount::try_deserialize_unchecked(&mut data.as_slice()).unwrap();
+        config.token_mint_address = Pubkey::new_unique();
+        config.token_price = 10;
+        config.pool_index = 2;
+        config.pause_flags = 1;
+        config
+    }
+
+    #[test]
+    fn pool_json_reports_the_effective_denominator() {
+        let address = Pubkey::new_unique();
+        let config = config();
+        let value = pool_json(&address, &config);
+        assert_eq!(value["address"], address.to_string());
+        assert_eq!(value["token_mint_address"], config.token_mint_address.to_string());
+        assert_eq!(value["token_price"], 10);
+        // a legacy pool stores no denominator and is priced per whole SOL
+        assert_eq!(value["price_denominator"], 1);
+        assert_eq!(value["pool_index"], 2);
+        assert_eq!(value["pause_flags"], 1);
+        assert_eq!(value["is_verified"], false);
+    }
+
+    #[test]
+    fn transaction_json_lists_accounts_in_order() {
+        let payer = Pubkey::new_unique();
+        let writable = Pubkey::new_unique();
+        let readonly = Pubkey::new_unique();
+        let instruction = Instruction {
+            program_id: swap::ID,
+            accounts: vec![AccountMeta::new(writable, false), AccountMeta::new_readonly(readonly, false)],
+            data: vec![1, 2, 3],
+        };
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer));
+        let value = transaction_json("drain", &transaction);
+        assert_eq!(value["command"], "drain");
+        assert_eq!(value["fee_payer"], payer.to_string());
+        let instruction = &value["instructions"][0];
+        assert_eq!(instruction["program_id"], swap::ID.to_string());
+        assert_eq!(instruction["data"], bs58::encode([1, 2, 3]).into_string());
+        let accounts = instruction["accounts"].as_array().unwrap();
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0]["pubkey"], writable.to_string());
+        assert_eq!(accounts[0]["is_writable"], true);
+        assert_eq!(accounts[0]["is_signer"], false);
+        assert_eq!(accounts[1]["pubkey"], readonly.to_string());
+        assert_eq!(accounts[1]["is_writable"], false);
+    }
+}
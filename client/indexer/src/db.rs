@@ -0,0 +1,404 @@
+use std::path::Path;
+
+use anyhow::Result;
+use anchor_lang::prelude::Pubkey;
+use rusqlite::{ params, Connection, OptionalExtension };
+use swap::state::WithdrawalKind;
+
+use crate::decode::{ IndexedTransaction, SwapEvent };
+
+// amounts are u64 on chain and stored as INTEGER, which sqlite keeps as i64
+const SCHEMA: &str =
+    "
+    CREATE TABLE IF NOT EXISTS transactions (
+        signature TEXT PRIMARY KEY,
+        slot INTEGER NOT NULL,
+        block_time INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS instructions (
+        signature TEXT NOT NULL,
+        ix_index INTEGER NOT NULL,
+        inner_index INTEGER, -- NULL for top-level instructions
+        name TEXT NOT NULL,
+        amount INTEGER,
+        UNIQUE (signature, ix_index, inner_index)
+    );
+    CREATE TABLE IF NOT EXISTS pools (
+        pool_config_account TEXT PRIMARY KEY,
+        token_mint_address TEXT NOT NULL,
+        pool_index INTEGER NOT NULL,
+        pool_token_account TEXT NOT NULL,
+        pool_native_account TEXT NOT NULL,
+        authority TEXT NOT NULL,
+        master_authority TEXT NOT NULL,
+        token_price INTEGER NOT NULL,
+        price_denominator INTEGER NOT NULL,
+        signature TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS deposits (
+        signature TEXT NOT NULL,
+        event_index INTEGER NOT NULL,
+        pool_config_account TEXT NOT NULL,
+        depositor TEXT NOT NULL,
+        amount INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL,
+        PRIMARY KEY (signature, event_index)
+    );
+    CREATE TABLE IF NOT EXISTS swaps (
+        signature TEXT NOT NULL,
+        event_index INTEGER NOT NULL,
+        pool_config_account TEXT NOT NULL,
+        user TEXT NOT NULL,
+        lamports_in INTEGER NOT NULL,
+        fee_lamports INTEGER NOT NULL,
+        tokens_out INTEGER NOT NULL,
+        token_price INTEGER NOT NULL,
+        price_denominator INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL,
+        PRIMARY KEY (signature, event_index)
+    );
+    CREATE TABLE IF NOT EXISTS withdrawals (
+        signature TEXT NOT NULL,
+        event_index INTEGER NOT NULL,
+        pool_config_account TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        authority TEXT NOT NULL,
+        destination TEXT NOT NULL,
+        amount INTEGER NOT NULL,
+        drained INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL,
+        PRIMARY KEY (signature, event_index)
+    );
+    ";
+
+pub struct PoolRow {
+    pub pool_config_account: String,
+    pub token_mint_address: String,
+    pub pool_index: u64,
+    pub pool_token_account: String,
+    pub pool_native_account: String,
+}
+
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct PoolTotals {
+    pub deposited: u64,
+    pub tokens_out: u64,
+    pub tokens_withdrawn: u64,
+    pub lamports_in: u64,
+    pub lamports_withdrawn: u64,
+}
+
+impl PoolTotals {
+    // signed so an over-withdrawn index shows up as a negative expectation instead of wrapping
+    pub fn expected_tokens(&self) -> i128 {
+        (self.deposited as i128) - (self.tokens_out as i128) - (self.tokens_withdrawn as i128)
+    }
+
+    // the native vault is created holding the rent-exempt minimum
+    pub fn expected_lamports(&self, rent_exempt_minimum: u64) -> i128 {
+        (rent_exempt_minimum as i128) + (self.lamports_in as i128) -
+            (self.lamports_withdrawn as i128)
+    }
+}
+
+fn kind_name(kind: WithdrawalKind) -> &'static str {
+    match kind {
+        WithdrawalKind::Token => "token",
+        WithdrawalKind::Native => "native",
+    }
+}
+
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    // the most recently indexed transaction, where an rpc sync resumes from
+    pub fn latest_signature(&self) -> Result<Option<String>> {
+        Ok(
+            self.conn
+                .query_row(
+                    "SELECT signature FROM transactions ORDER BY slot DESC, rowid DESC LIMIT 1",
+                    [],
+                    |row| row.get(0)
+                )
+                .optional()?
+        )
+    }
+
+    // returns false when the transaction was already indexed, so re-ingesting is harmless
+    pub fn record(&mut self, transaction: &IndexedTransaction) -> Result<bool> {
+        let tx = self.conn.transaction()?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time) VALUES (?1, ?2, ?3)",
+            params![transaction.signature, transaction.slot as i64, transaction.block_time]
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+        let signature = &transaction.signature;
+
+        for indexed in &transaction.instructions {
+            tx.execute(
+                "INSERT INTO instructions (signature, ix_index, inner_index, name, amount) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    signature,
+                    indexed.index as i64,
+                    indexed.inner_index.map(|inner_index| inner_index as i64),
+                    indexed.instruction.name,
+                    indexed.instruction.amount.map(|amount| amount as i64)
+                ]
+            )?;
+        }
+
+        for (event_index, event) in transaction.events.iter().enumerate() {
+            let pool = event.pool_config_account().to_string();
+            match event {
+                SwapEvent::PoolInitialized(event) => {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO pools (pool_config_account, token_mint_address, pool_index, pool_token_account, pool_native_account, authority, master_authority, token_price, price_denominator, signature, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                        params![
+                            pool,
+                            event.token_mint_address.to_string(),
+                            event.pool_index as i64,
+                            event.pool_token_account.to_string(),
+                            event.pool_native_account.to_string(),
+                            event.authority.to_string(),
+                            event.master_authority.to_string(),
+                            event.token_price as i64,
+                            event.price_denominator as i64,
+                            signature,
+                            event.timestamp
+                        ]
+                    )?;
+                }
+                SwapEvent::LiquidityAdded(event) => {
+                    tx.execute(
+                        "INSERT INTO deposits (signature, event_index, pool_config_account, depositor, amount, timestamp) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            signature,
+                            event_index as i64,
+                            pool,
+                            event.depositor.to_string(),
+                            event.amount as i64,
+                            event.timestamp
+                        ]
+                    )?;
+                }
+                SwapEvent::TokensSwapped(event) => {
+                    tx.execute(
+                        "INSERT INTO swaps (signature, event_index, pool_config_account, user, lamports_in, fee_lamports, tokens_out, token_price, price_denominator, timestamp) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                        params![
+                            signature,
+                            event_index as i64,
+                            pool,
+                            event.user.to_string(),
+                            event.lamports_in as i64,
+                            event.fee_lamports as i64,
+                            event.tokens_out as i64,
+                            event.token_price as i64,
+                            event.price_denominator as i64,
+                            event.timestamp
+                        ]
+                    )?;
+                }
+                SwapEvent::TokenWithdrawn(event) => {
+                    insert_withdrawal(&tx, signature, event_index, &pool, &Withdrawal {
+                        kind: WithdrawalKind::Token,
+                        authority: event.authority,
+                        destination: event.destination,
+                        amount: event.amount,
+                        drained: false,
+                        timestamp: event.timestamp,
+                    })?;
+                }
+                SwapEvent::NativeWithdrawn(event) => {
+                    insert_withdrawal(&tx, signature, event_index, &pool, &Withdrawal {
+                        kind: WithdrawalKind::Native,
+                        authority: event.authority,
+                        destination: event.destination,
+                        amount: event.lamports,
+                        drained: false,
+                        timestamp: event.timestamp,
+                    })?;
+                }
+                SwapEvent::PoolDrained(event) => {
+                    insert_withdrawal(&tx, signature, event_index, &pool, &Withdrawal {
+                        kind: event.kind,
+                        authority: event.authority,
+                        destination: event.destination,
+                        amount: event.amount,
+                        drained: true,
+                        timestamp: event.timestamp,
+                    })?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    pub fn pools(&self) -> Result<Vec<PoolRow>> {
+        let mut statement = self.conn.prepare(
+            "SELECT pool_config_account, token_mint_address, pool_index, pool_token_account, pool_native_account FROM pools ORDER BY created_at, rowid"
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok(PoolRow {
+                pool_config_account: row.get(0)?,
+                token_mint_address: row.get(1)?,
+                pool_index: row.get::<_, i64>(2)? as u64,
+                pool_token_account: row.get(3)?,
+                pool_native_account: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    // sums run in sqlite's i64, fine for anything a single mint can hold
+    pub fn totals(&self, pool_config_account: &str) -> Result<PoolTotals> {
+        let sum = |sql: &str| -> Result<u64> {
+            let total: i64 = self.conn.query_row(sql, [pool_config_account], |row| row.get(0))?;
+            Ok(total as u64)
+        };
+        Ok(PoolTotals {
+            deposited: sum(
+                "SELECT COALESCE(SUM(amount), 0) FROM deposits WHERE pool_config_account = ?1"
+            )?,
+            tokens_out: sum(
+                "SELECT COALESCE(SUM(tokens_out), 0) FROM swaps WHERE pool_config_account = ?1"
+            )?,
+            tokens_withdrawn: sum(
+                "SELECT COALESCE(SUM(amount), 0) FROM withdrawals WHERE pool_config_account = ?1 AND kind = 'token'"
+            )?,
+            lamports_in: sum(
+                "SELECT COALESCE(SUM(lamports_in), 0) FROM swaps WHERE pool_config_account = ?1"
+            )?,
+            lamports_withdrawn: sum(
+                "SELECT COALESCE(SUM(amount), 0) FROM withdrawals WHERE pool_config_account = ?1 AND kind = 'native'"
+            )?,
+        })
+    }
+}
+
+struct Withdrawal {
+    kind: WithdrawalKind,
+    authority: Pubkey,
+    destination: Pubkey,
+    amount: u64,
+    drained: bool,
+    timestamp: i64,
+}
+
+fn insert_withdrawal(
+    tx: &rusqlite::Transaction,
+    signature: &str,
+    event_index: usize,
+    pool: &str,
+    withdrawal: &Withdrawal
+) -> Result<()> {
+    tx.execute(
+        "INSERT INTO withdrawals (signature, event_index, pool_config_account, kind, authority, destination, amount, drained, timestamp) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            signature,
+            event_index as i64,
+            pool,
+            kind_name(withdrawal.kind),
+            withdrawal.authority.to_string(),
+            withdrawal.destination.to_string(),
+            withdrawal.amount as i64,
+            withdrawal.drained,
+            withdrawal.timestamp
+        ]
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swap::events::{ LiquidityAdded, PoolDrained, TokensSwapped };
+
+    fn transaction(signature: &str, events: Vec<SwapEvent>) -> IndexedTransaction {
+        IndexedTransaction {
+            signature: signature.to_string(),
+            slot: 1,
+            block_time: None,
+            instructions: vec![],
+            events,
+        }
+    }
+
+    #[test]
+    fn totals_follow_recorded_events() {
+        let mut db = Db::open_in_memory().unwrap();
+        let pool = Pubkey::new_unique();
+        let depositor = Pubkey::new_unique();
+
+        let deposit = transaction("deposit", vec![
+            SwapEvent::LiquidityAdded(LiquidityAdded {
+                pool_config_account: pool,
+                depositor,
+                amount: 1_000,
+                pool_token_balance: 1_000,
+                timestamp: 0,
+            })
+        ]);
+        assert!(db.record(&deposit).unwrap());
+        // ingesting the same signature twice is a no-op
+        assert!(!db.record(&deposit).unwrap());
+
+        db.record(
+            &transaction("swap", vec![
+                SwapEvent::TokensSwapped(TokensSwapped {
+                    pool_config_account: pool,
+                    user: depositor,
+                    lamports_in: 50,
+                    fee_lamports: 1,
+                    tokens_out: 400,
+                    token_price: 10,
+                    price_denominator: 1,
+                    timestamp: 0,
+                })
+            ])
+        ).unwrap();
+        db.record(
+            &transaction("drain", vec![
+                SwapEvent::PoolDrained(PoolDrained {
+                    pool_config_account: pool,
+                    authority: depositor,
+                    kind: WithdrawalKind::Native,
+                    destination: depositor,
+                    amount: 50,
+                    timestamp: 0,
+                })
+            ])
+        ).unwrap();
+
+        let totals = db.totals(&pool.to_string()).unwrap();
+        assert_eq!(totals, PoolTotals {
+            deposited: 1_000,
+            tokens_out: 400,
+            tokens_withdrawn: 0,
+            lamports_in: 50,
+            lamports_withdrawn: 50,
+        });
+        assert_eq!(totals.expected_tokens(), 600);
+        assert_eq!(totals.expected_lamports(890_880), 890_880);
+        assert_eq!(db.latest_signature().unwrap().as_deref(), Some("drain"));
+    }
+}
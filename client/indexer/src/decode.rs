@@ -0,0 +1,339 @@
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{ AnchorDeserialize, Discriminator };
+use swap::events::{
+    LiquidityAdded,
+    NativeWithdrawn,
+    PoolDrained,
+    PoolInitialized,
+    TokenWithdrawn,
+    TokensSwapped,
+};
+
+use crate::source::{ RpcInstruction, RpcTransaction };
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+pub struct DecodedInstruction {
+    pub name: &'static str,
+    pub amount: Option<u64>, // the requested amount for instructions that take one
+}
+
+pub enum SwapEvent {
+    PoolInitialized(PoolInitialized),
+    LiquidityAdded(LiquidityAdded),
+    TokensSwapped(TokensSwapped),
+    TokenWithdrawn(TokenWithdrawn),
+    NativeWithdrawn(NativeWithdrawn),
+    PoolDrained(PoolDrained),
+}
+
+pub struct IndexedInstruction {
+    pub index: usize,
+    pub inner_index: Option<usize>, // position among the cpis made by top-level instruction `index`
+    pub instruction: DecodedInstruction,
+}
+
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub instructions: Vec<IndexedInstruction>,
+    pub events: Vec<SwapEvent>,
+}
+
+// anchor prefixes instruction data and events with an 8 byte discriminator
+fn decode_as<T: AnchorDeserialize + Discriminator>(data: &[u8]) -> Option<T> {
+    if data.len() < 8 || data[..8] != T::DISCRIMINATOR {
+        return None;
+    }
+    T::try_from_slice(&data[8..]).ok()
+}
+
+macro_rules! decode_instructions {
+    ($data:expr, $($ty:ident => $name:literal $(($field:ident))?),* $(,)?) => {
+        $(
+            if let Some(ix) = decode_as::<swap::instruction::$ty>($data) {
+                return Some(DecodedInstruction {
+                    name: $name,
+                    amount: decode_instructions!(@amount ix $(, $field)?),
+                });
+            }
+        )*
+    };
+    (@amount $ix:ident) => {
+        { let _ = $ix; None }
+    };
+    (@amount $ix:ident, $field:ident) => {
+        Some($ix.$field)
+    };
+}
+
+pub fn decode_instruction(data: &[u8]) -> Option<DecodedInstruction> {
+    decode_instructions!(data,
+        InitInstruction => "init_instruction",
+        UpdatePoolParamsInstruction => "update_pool_params_instruction",
+        SetPauseInstruction => "set_pause_instruction",
+        SetGuardianInstruction => "set_guardian_instruction",
+        GuardianPauseInstruction => "guardian_pause_instruction",
+        GrantRoleInstruction => "grant_role_instruction",
+        RevokeRoleInstruction => "revoke_role_instruction",
+        MigratePoolInstruction => "migrate_pool_instruction",
+        InitProgramConfigInstruction => "init_program_config_instruction",
+        UpdateProgramConfigInstruction => "update_program_config_instruction",
+        SetGlobalPauseInstruction => "set_global_pause_instruction",
+        VerifyPoolInstruction => "verify_pool_instruction",
+        AddLiquidInstruction => "add_liquid_instruction" (amount),
+        SwapToken => "swap_token" (lamport_amount),
+        QuoteSwap => "quote_swap" (lamport_amount),
+        WithdrawTokenInstruction => "withdraw_token_instruction" (amount),
+        DrainTokenInstruction => "drain_token_instruction",
+        WithdrawNativeInstruction => "withdraw_native_instruction" (amount),
+        DrainNativeInstruction => "drain_native_instruction",
+        ProposeAuthorityInstruction => "propose_authority_instruction",
+        AcceptAuthorityInstruction => "accept_authority_instruction",
+        ProposeMasterAuthorityInstruction => "propose_master_authority_instruction",
+        AcceptMasterAuthorityInstruction => "accept_master_authority_instruction",
+        SetMultisigInstruction => "set_multisig_instruction",
+        SetWithdrawalDelayInstruction => "set_withdrawal_delay_instruction",
+        QueueWithdrawalInstruction => "queue_withdrawal_instruction" (amount),
+        ExecuteWithdrawalInstruction => "execute_withdrawal_instruction",
+        CancelWithdrawalInstruction => "cancel_withdrawal_instruction",
+        SetWithdrawDestinationsInstruction => "set_withdraw_destinations_instruction",
+    );
+    None
+}
+
+// events carry the settled amounts, instruction args only carry what was asked for
+pub fn decode_event(data: &[u8]) -> Option<SwapEvent> {
+    decode_as(data)
+        .map(SwapEvent::PoolInitialized)
+        .or_else(|| decode_as(data).map(SwapEvent::LiquidityAdded))
+        .or_else(|| decode_as(data).map(SwapEvent::TokensSwapped))
+        .or_else(|| decode_as(data).map(SwapEvent::TokenWithdrawn))
+        .or_else(|| decode_as(data).map(SwapEvent::NativeWithdrawn))
+        .or_else(|| decode_as(data).map(SwapEvent::PoolDrained))
+}
+
+fn decode_log(line: &str) -> Option<SwapEvent> {
+    let data = base64::decode(line.strip_prefix(PROGRAM_DATA)?).ok()?;
+    decode_event(&data)
+}
+
+// any program can log "Program data:", so only lines written while the swap program is on top
+// of the invoke stack count. program output is always prefixed with "Program log:" or
+// "Program data:", neither parses as a program id, so it can't push or pop the stack
+fn decode_logs(logs: &[String]) -> Vec<SwapEvent> {
+    let mut stack: Vec<Pubkey> = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        if line.starts_with(PROGRAM_DATA) {
+            if stack.last() == Some(&swap::ID) {
+                events.extend(decode_log(line));
+            }
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        if words.next() != Some("Program") {
+            continue;
+        }
+        let program_id = match words.next().map(Pubkey::from_str) {
+            Some(Ok(program_id)) => program_id,
+            _ => {
+                continue;
+            }
+        };
+        match words.next() {
+            Some("invoke") => stack.push(program_id),
+            Some("success") | Some("failed:") => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+fn decode_program_instruction(
+    account_keys: &[String],
+    instruction: &RpcInstruction
+) -> Option<DecodedInstruction> {
+    let program_id = account_keys.get(instruction.program_id_index as usize)?;
+    if *program_id != swap::ID.to_string() {
+        return None;
+    }
+    let data = bs58::decode(&instruction.data).into_vec().ok()?;
+    decode_instruction(&data)
+}
+
+// failed transactions and ones that never touch the program are skipped
+pub fn decode_transaction(transaction: &RpcTransaction) -> Option<IndexedTransaction> {
+    let meta = transaction.meta.as_ref()?;
+    if meta.err.is_some() {
+        return None;
+    }
+    let account_keys = transaction.account_keys();
+    let mut instructions: Vec<IndexedInstruction> = transaction.transaction.message.instructions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, instruction)| {
+            Some(IndexedInstruction {
+                index,
+                inner_index: None,
+                instruction: decode_program_instruction(&account_keys, instruction)?,
+            })
+        })
+        .collect();
+    // calls into the program made by other programs
+    for inner in meta.inner_instructions.iter().flatten() {
+        for (inner_index, instruction) in inner.instructions.iter().enumerate() {
+            if let Some(decoded) = decode_program_instruction(&account_keys, instruction) {
+                instructions.push(IndexedInstruction {
+                    index: inner.index as usize,
+                    inner_index: Some(inner_index),
+                    instruction: decoded,
+                });
+            }
+        }
+    }
+    instructions.sort_by_key(|indexed| (indexed.index, indexed.inner_index));
+    let events = decode_logs(meta.log_messages.as_deref().unwrap_or_default());
+    if instructions.is_empty() && events.is_empty() {
+        return None;
+    }
+
+    Some(IndexedTransaction {
+        signature: transaction.transaction.signatures.first()?.clone(),
+        slot: transaction.slot,
+        block_time: transaction.block_time,
+        instructions,
+        events,
+    })
+}
+
+impl SwapEvent {
+    pub fn pool_config_account(&self) -> Pubkey {
+        match self {
+            SwapEvent::PoolInitialized(event) => event.pool_config_account,
+            SwapEvent::LiquidityAdded(event) => event.pool_config_account,
+            SwapEvent::TokensSwapped(event) => event.pool_config_account,
+            SwapEvent::TokenWithdrawn(event) => event.pool_config_account,
+            SwapEvent::NativeWithdrawn(event) => event.pool_config_account,
+            SwapEvent::PoolDrained(event) => event.pool_config_account,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{ AnchorSerialize, InstructionData };
+
+    #[test]
+    fn decodes_instructions_by_discriminator() {
+        let data = swap::instruction::AddLiquidInstruction { amount: 42 }.data();
+        let decoded = decode_instruction(&data).unwrap();
+        assert_eq!(decoded.name, "add_liquid_instruction");
+        assert_eq!(decoded.amount, Some(42));
+
+        let data = swap::instruction::DrainNativeInstruction {}.data();
+        let decoded = decode_instruction(&data).unwrap();
+        assert_eq!(decoded.name, "drain_native_instruction");
+        assert_eq!(decoded.amount, None);
+
+        assert!(decode_instruction(&[0; 16]).is_none());
+    }
+
+    fn swapped_log(pool_config_account: Pubkey) -> String {
+        let event = TokensSwapped {
+            pool_config_account,
+            user: Pubkey::new_unique(),
+            lamports_in: 500,
+            fee_lamports: 5,
+            tokens_out: 4_950,
+            token_price: 10,
+            price_denominator: 1,
+            timestamp: 1,
+        };
+        let mut data = TokensSwapped::DISCRIMINATOR.to_vec();
+        event.serialize(&mut data).unwrap();
+        format!("{}{}", PROGRAM_DATA, base64::encode(data))
+    }
+
+    #[test]
+    fn decodes_events_from_logs() {
+        let pool_config_account = Pubkey::new_unique();
+        match decode_log(&swapped_log(pool_config_account)) {
+            Some(SwapEvent::TokensSwapped(decoded)) => {
+                assert_eq!(decoded.pool_config_account, pool_config_account);
+                assert_eq!(decoded.tokens_out, 4_950);
+            }
+            _ => panic!("expected a TokensSwapped event"),
+        }
+        assert!(decode_log("Program log: Instruction: SwapToken").is_none());
+    }
+
+    #[test]
+    fn ignores_events_logged_by_other_programs() {
+        let other = Pubkey::new_unique();
+        let token_program = Pubkey::new_unique();
+        let spoofed = Pubkey::new_unique();
+        let real = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {} invoke [1]", other),
+            swapped_log(spoofed),
+            "Program log: Program 11111111111111111111111111111111 success".to_string(),
+            format!("Program {} invoke [2]", swap::ID),
+            format!("Program {} invoke [3]", token_program),
+            swapped_log(spoofed),
+            format!("Program {} success", token_program),
+            swapped_log(real),
+            format!("Program {} consumed 20000 of 200000 compute units", swap::ID),
+            format!("Program {} success", swap::ID),
+            swapped_log(spoofed),
+            format!("Program {} success", other),
+        ];
+        let events = decode_logs(&logs);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].pool_config_account(), real);
+    }
+
+    #[test]
+    fn decodes_program_calls_made_through_cpi() {
+        let other = Pubkey::new_unique();
+        let data = swap::instruction::AddLiquidInstruction { amount: 42 }.data();
+        let data = bs58::encode(data).into_string();
+        let transaction: RpcTransaction = serde_json::from_value(
+            serde_json::json!({
+                "slot": 1,
+                "blockTime": null,
+                "transaction": {
+                    "signatures": ["sig"],
+                    "message": {
+                        "accountKeys": [other.to_string(), swap::ID.to_string()],
+                        "instructions": [{ "programIdIndex": 0, "accounts": [], "data": "" }],
+                    },
+                },
+                "meta": {
+                    "err": null,
+                    "logMessages": [],
+                    "innerInstructions": [
+                        {
+                            "index": 0,
+                            "instructions": [
+                                { "programIdIndex": 0, "accounts": [], "data": "" },
+                                { "programIdIndex": 1, "accounts": [], "data": data },
+                            ],
+                        },
+                    ],
+                },
+            })
+        ).unwrap();
+        let indexed = decode_transaction(&transaction).unwrap();
+        assert_eq!(indexed.instructions.len(), 1);
+        assert_eq!(indexed.instructions[0].index, 0);
+        assert_eq!(indexed.instructions[0].inner_index, Some(1));
+        assert_eq!(indexed.instructions[0].instruction.amount, Some(42));
+    }
+}
@@ -0,0 +1,126 @@
+mod db;
+mod decode;
+mod source;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use anyhow::Result;
+use clap::{ Parser, Subcommand };
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::db::Db;
+use crate::decode::decode_transaction;
+use crate::source::RpcTransaction;
+
+#[derive(Parser)]
+#[command(name = "swap-indexer", about = "Index swap program transactions into SQLite")]
+struct Cli {
+    /// SQLite database, created on first use
+    #[arg(long, global = true, default_value = "swap-index.sqlite")]
+    db: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Ingest `getTransaction` results (json encoding) saved to files
+    Ingest {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Fetch and ingest every program transaction since the last indexed one
+    Sync {
+        #[arg(short = 'u', long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Check indexed totals against the vault balances of every indexed pool
+    Reconcile {
+        #[arg(short = 'u', long, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+}
+
+fn ingest(db: &mut Db, transactions: &[RpcTransaction]) -> Result<()> {
+    let mut indexed = 0;
+    for transaction in transactions {
+        if let Some(decoded) = decode_transaction(transaction) {
+            if db.record(&decoded)? {
+                indexed += 1;
+            }
+        }
+    }
+    println!("indexed {} of {} transactions", indexed, transactions.len());
+    Ok(())
+}
+
+// returns whether every pool matched
+fn reconcile(db: &Db, client: &RpcClient) -> Result<bool> {
+    let rent_exempt_minimum = client.get_minimum_balance_for_rent_exemption(0)?;
+    let mut matched = true;
+    for pool in db.pools()? {
+        let totals = db.totals(&pool.pool_config_account)?;
+        let token_balance = client
+            .get_token_account_balance(&Pubkey::from_str(&pool.pool_token_account)?)?
+            .amount.parse::<u64>()?;
+        let native_balance = client.get_balance(&Pubkey::from_str(&pool.pool_native_account)?)?;
+
+        let expected_tokens = totals.expected_tokens();
+        let expected_lamports = totals.expected_lamports(rent_exempt_minimum);
+        let tokens_ok = expected_tokens == (token_balance as i128);
+        let lamports_ok = expected_lamports == (native_balance as i128);
+        matched &= tokens_ok && lamports_ok;
+
+        println!(
+            "{} (mint {}, index {})",
+            pool.pool_config_account,
+            pool.token_mint_address,
+            pool.pool_index
+        );
+        println!(
+            "  tokens:   indexed {} vault {} {}",
+            expected_tokens,
+            token_balance,
+            if tokens_ok { "ok" } else { "MISMATCH" }
+        );
+        println!(
+            "  lamports: indexed {} vault {} {}",
+            expected_lamports,
+            native_balance,
+            if lamports_ok { "ok" } else { "MISMATCH" }
+        );
+    }
+    Ok(matched)
+}
+
+fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
+    let mut db = Db::open(&cli.db)?;
+
+    match cli.command {
+        Command::Ingest { files } => {
+            let mut transactions = Vec::new();
+            for file in &files {
+                transactions.extend(source::read_file(file)?);
+            }
+            ingest(&mut db, &transactions)?;
+        }
+        Command::Sync { url } => {
+            let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            let until = db.latest_signature()?;
+            let transactions = source::fetch_transactions(&client, until.as_deref())?;
+            ingest(&mut db, &transactions)?;
+        }
+        Command::Reconcile { url } => {
+            let client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            if !reconcile(&db, &client)? {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
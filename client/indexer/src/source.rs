@@ -0,0 +1,149 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{ Context, Result };
+use serde::Deserialize;
+use serde_json::{ json, Value };
+use solana_client::rpc_client::{ GetConfirmedSignaturesForAddress2Config, RpcClient };
+use solana_client::rpc_request::RpcRequest;
+use solana_sdk::signature::Signature;
+
+// the `getTransaction` result with "json" encoding
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransaction {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub transaction: RpcInnerTransaction,
+    pub meta: Option<RpcTransactionMeta>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcInnerTransaction {
+    pub signatures: Vec<String>,
+    pub message: RpcMessage,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcMessage {
+    pub account_keys: Vec<String>,
+    pub instructions: Vec<RpcInstruction>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcInstruction {
+    pub program_id_index: u8,
+    pub data: String, // base58
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionMeta {
+    pub err: Option<Value>,
+    pub log_messages: Option<Vec<String>>,
+    pub inner_instructions: Option<Vec<RpcInnerInstructions>>,
+    pub loaded_addresses: Option<RpcLoadedAddresses>,
+}
+
+#[derive(Deserialize)]
+pub struct RpcInnerInstructions {
+    pub index: u8, // the top-level instruction that made these calls
+    pub instructions: Vec<RpcInstruction>,
+}
+
+#[derive(Deserialize)]
+pub struct RpcLoadedAddresses {
+    pub writable: Vec<String>,
+    pub readonly: Vec<String>,
+}
+
+impl RpcTransaction {
+    // versioned transactions resolve lookup table keys after the static ones
+    pub fn account_keys(&self) -> Vec<String> {
+        let mut keys = self.transaction.message.account_keys.clone();
+        if let Some(loaded) = self.meta.as_ref().and_then(|meta| meta.loaded_addresses.as_ref()) {
+            keys.extend(loaded.writable.iter().cloned());
+            keys.extend(loaded.readonly.iter().cloned());
+        }
+        keys
+    }
+}
+
+// accepts a bare result, a JSON-RPC response envelope, or an array of either
+fn collect(value: Value, transactions: &mut Vec<RpcTransaction>) -> Result<()> {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                collect(value, transactions)?;
+            }
+        }
+        Value::Object(mut object) if object.contains_key("jsonrpc") => {
+            if let Some(result) = object.remove("result") {
+                collect(result, transactions)?;
+            }
+        }
+        Value::Null => {}
+        value => transactions.push(serde_json::from_value(value)?),
+    }
+    Ok(())
+}
+
+// a single JSON document, or one document per line
+pub fn read_file(path: &Path) -> Result<Vec<RpcTransaction>> {
+    let contents = std::fs::read_to_string(path).with_context(|| {
+        format!("failed to read {}", path.display())
+    })?;
+    let mut transactions = Vec::new();
+    match serde_json::from_str::<Value>(&contents) {
+        Ok(value) => collect(value, &mut transactions)?,
+        Err(_) => {
+            for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+                collect(serde_json::from_str(line)?, &mut transactions)?;
+            }
+        }
+    }
+    Ok(transactions)
+}
+
+// every program transaction after `until`, oldest first
+pub fn fetch_transactions(client: &RpcClient, until: Option<&str>) -> Result<Vec<RpcTransaction>> {
+    let until = until.map(Signature::from_str).transpose()?;
+    let mut signatures = Vec::new();
+    let mut before = None;
+    loop {
+        let page = client.get_signatures_for_address_with_config(
+            &swap::ID,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: None,
+                commitment: Some(client.commitment()),
+            }
+        )?;
+        match page.last() {
+            Some(last) => before = Some(Signature::from_str(&last.signature)?),
+            None => break,
+        }
+        signatures.extend(page.into_iter().map(|status| status.signature));
+    }
+
+    let mut transactions = Vec::with_capacity(signatures.len());
+    for signature in signatures.iter().rev() {
+        let value: Value = client.send(
+            RpcRequest::GetTransaction,
+            json!([
+                signature,
+                {
+                    "encoding": "json",
+                    "commitment": client.commitment().commitment,
+                    "maxSupportedTransactionVersion": 0,
+                },
+            ])
+        )?;
+        collect(value, &mut transactions)?;
+    }
+    Ok(transactions)
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use solana_client::rpc_client::RpcClient;
+use swap::state::{ PoolConfigAccount, PoolRegistryEntry, ProgramConfig };
+use thiserror::Error;
+
+use crate::errors::{ decode_client_error, ProgramError };
+use crate::pda::{ find_pool_registry_entry, find_program_config };
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("rpc error: {0}")]
+    Rpc(#[from] Box<solana_client::client_error::ClientError>),
+    #[error("failed to decode account {0}: {1}")]
+    Decode(Pubkey, Box<anchor_lang::error::Error>),
+    #[error("account {0} is not owned by the swap program")]
+    InvalidOwner(Pubkey),
+    #[error("quote failed: {0:?}")]
+    Quote(swap::quote::QuoteError),
+    #[error("simulation failed: {0}")]
+    Simulation(String),
+    #[error("program error: {0}")]
+    Program(Box<ProgramError>),
+}
+
+// failed transactions surface as the program error when it can be decoded
+impl From<solana_client::client_error::ClientError> for ClientError {
+    fn from(err: solana_client::client_error::ClientError) -> Self {
+        match decode_client_error(&err) {
+            Some(program_error) => Self::Program(Box::new(program_error)),
+            None => Self::Rpc(Box::new(err)),
+        }
+    }
+}
+
+// checks the anchor discriminator before decoding
+pub fn decode_account<T: AccountDeserialize>(address: &Pubkey, data: &[u8]) -> Result<T, ClientError> {
+    let mut data = data;
+    T::try_deserialize(&mut data).map_err(|err| ClientError::Decode(*address, Box::new(err)))
+}
+
+pub fn fetch_account<T: AccountDeserialize>(
+    client: &RpcClient,
+    address: &Pubkey
+) -> Result<T, ClientError> {
+    let account = client.get_account(address)?;
+    if account.owner != swap::ID {
+        return Err(ClientError::InvalidOwner(*address));
+    }
+    decode_account(address, &account.data)
+}
+
+pub fn fetch_pool_config(
+    client: &RpcClient,
+    pool_config_account: &Pubkey
+) -> Result<PoolConfigAccount, ClientError> {
+    fetch_account(client, pool_config_account)
+}
+
+pub fn fetch_program_config(client: &RpcClient) -> Result<ProgramConfig, ClientError> {
+    fetch_account(client, &find_program_config().0)
+}
+
+// walks the registry in creation order
+pub fn fetch_pools(client: &RpcClient) -> Result<Vec<(Pubkey, PoolConfigAccount)>, ClientError> {
+    let program_config = fetch_program_config(client)?;
+    let mut pools = Vec::with_capacity(program_config.pool_count as usize);
+    for index in 0..program_config.pool_count {
+        let entry: PoolRegistryEntry = fetch_account(client, &find_pool_registry_entry(index).0)?;
+        let config = fetch_pool_config(client, &entry.pool_config_account)?;
+        pools.push((entry.pool_config_account, config));
+    }
+    Ok(pools)
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+
+use solana_client::client_error::{ ClientError as RpcClientError, ClientErrorKind };
+use solana_client::rpc_request::{ RpcError, RpcResponseErrorData };
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+use swap::error::CustomError;
+
+// anchor framework codes sit below the user offset, smaller codes come from other programs
+const ANCHOR_ERROR_CODE_MIN: u32 = 100;
+
+#[derive(Clone, Copy, Debug)]
+pub enum ProgramErrorKind {
+    Swap(CustomError),
+    Anchor,
+    Unknown,
+}
+
+// a failed instruction mapped back to the program error that caused it
+#[derive(Clone, Debug)]
+pub struct ProgramError {
+    pub instruction_index: u8,
+    pub code: u32,
+    pub kind: ProgramErrorKind,
+    pub name: Option<String>,
+    pub message: Option<String>,
+    // only anchor account constraints log the failing account
+    pub account: Option<String>,
+}
+
+impl ProgramError {
+    pub fn custom(&self) -> Option<CustomError> {
+        match self.kind {
+            ProgramErrorKind::Swap(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "instruction {} failed", self.instruction_index)?;
+        if let Some(account) = &self.account {
+            write!(f, " on account {}", account)?;
+        }
+        match &self.name {
+            Some(name) => write!(f, ": {} ({})", name, self.code)?,
+            None => write!(f, ": custom program error {:#x}", self.code)?,
+        }
+        if let Some(message) = &self.message {
+            write!(f, ", {}", message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ProgramError {}
+
+struct AnchorLog {
+    account: Option<String>,
+    name: String,
+    number: u32,
+    message: String,
+}
+
+// "AnchorError caused by account: user. Error Code: ConstraintRaw. Error Number: 2003. Error Message: ..."
+fn parse_anchor_log(line: &str) -> Option<AnchorLog> {
+    let line = &line[line.find("AnchorError ")?..];
+    let account = line
+        .strip_prefix("AnchorError caused by account: ")
+        .and_then(|rest| rest.split_once(". Error Code: "))
+        .map(|(account, _)| account.to_string());
+    let (_, rest) = line.split_once("Error Code: ")?;
+    let (name, rest) = rest.split_once(". Error Number: ")?;
+    let (number, message) = rest.split_once(". Error Message: ")?;
+
+    Some(AnchorLog {
+        account,
+        name: name.to_string(),
+        number: number.parse().ok()?,
+        message: message.trim_end_matches('.').to_string(),
+    })
+}
+
+pub fn decode_error_code(code: u32) -> ProgramErrorKind {
+    match CustomError::from_code(code) {
+        Some(error) => ProgramErrorKind::Swap(error),
+        None if (ANCHOR_ERROR_CODE_MIN..anchor_lang::error::ERROR_CODE_OFFSET).contains(&code) => {
+            ProgramErrorKind::Anchor
+        }
+        None => ProgramErrorKind::Unknown,
+    }
+}
+
+// logs are optional, they only add the anchor name and the failing account
+pub fn decode_transaction_error(err: &TransactionError, logs: &[String]) -> Option<ProgramError> {
+    let (instruction_index, code) = match err {
+        TransactionError::InstructionError(index, InstructionError::Custom(code)) => {
+            (*index, *code)
+        }
+        _ => {
+            return None;
+        }
+    };
+    let kind = decode_error_code(code);
+    let log = logs
+        .iter()
+        .rev()
+        .filter_map(|line| parse_anchor_log(line))
+        .find(|log| log.number == code);
+    let (name, message, account) = match (kind, log) {
+        (_, Some(log)) => (Some(log.name), Some(log.message), log.account),
+        (ProgramErrorKind::Swap(error), None) => (Some(error.name()), Some(error.to_string()), None),
+        _ => (None, None, None),
+    };
+
+    Some(ProgramError { instruction_index, code, kind, name, message, account })
+}
+
+// covers both preflight failures and errors returned after sending
+pub fn decode_client_error(err: &RpcClientError) -> Option<ProgramError> {
+    let logs = match &err.kind {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => result.logs.clone().unwrap_or_default(),
+        _ => vec![],
+    };
+    decode_transaction_error(&err.get_transaction_error()?, &logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_swap_errors_without_logs() {
+        let err = TransactionError::InstructionError(1, InstructionError::Custom(6034));
+        let decoded = decode_transaction_error(&err, &[]).unwrap();
+        assert_eq!(decoded.instruction_index, 1);
+        assert!(matches!(decoded.custom(), Some(CustomError::InsufficientUserLamports)));
+        assert_eq!(decoded.name.as_deref(), Some("InsufficientUserLamports"));
+        assert_eq!(decoded.message.as_deref(), Some("user insufficient lamports"));
+        assert!(decoded.account.is_none());
+    }
+
+    #[test]
+    fn takes_the_failing_account_from_logs() {
+        let logs = vec![
+            "Program log: Instruction: SwapToken".to_string(),
+            "Program log: AnchorError caused by account: user. Error Code: InsufficientUserLamports. Error Number: 6034. Error Message: user insufficient lamports.".to_string(),
+        ];
+        let err = TransactionError::InstructionError(0, InstructionError::Custom(6034));
+        let decoded = decode_transaction_error(&err, &logs).unwrap();
+        assert_eq!(decoded.account.as_deref(), Some("user"));
+        assert_eq!(
+            decoded.to_string(),
+            "instruction 0 failed on account user: InsufficientUserLamports (6034), user insufficient lamports"
+        );
+
+        let logs = vec![
+            "Program log: AnchorError caused by account: pool_config_account. Error Code: ConstraintSeeds. Error Number: 2006. Error Message: A seeds constraint was violated.".to_string(),
+        ];
+        let err = TransactionError::InstructionError(0, InstructionError::Custom(2006));
+        let decoded = decode_transaction_error(&err, &logs).unwrap();
+        assert!(matches!(decoded.kind, ProgramErrorKind::Anchor));
+        assert_eq!(decoded.name.as_deref(), Some("ConstraintSeeds"));
+        assert_eq!(decoded.account.as_deref(), Some("pool_config_account"));
+    }
+
+    #[test]
+    fn leaves_other_programs_and_errors_alone() {
+        // spl token's InsufficientFunds failing inside a cpi
+        let err = TransactionError::InstructionError(0, InstructionError::Custom(1));
+        let decoded = decode_transaction_error(&err, &[]).unwrap();
+        assert!(matches!(decoded.kind, ProgramErrorKind::Unknown));
+        assert_eq!(decoded.to_string(), "instruction 0 failed: custom program error 0x1");
+
+        let err = TransactionError::InstructionError(0, InstructionError::InvalidArgument);
+        assert!(decode_transaction_error(&err, &[]).is_none());
+        assert!(decode_transaction_error(&TransactionError::AccountNotFound, &[]).is_none());
+    }
+}
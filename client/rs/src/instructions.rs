@@ -0,0 +1,639 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{ AccountMeta, Instruction };
+use anchor_lang::solana_program::{ system_program, sysvar };
+use anchor_lang::{ InstructionData, ToAccountMetas };
+use anchor_spl::associated_token::{ self, get_associated_token_address };
+use anchor_spl::token;
+use swap::instructions::UpdatePoolParamsArgs;
+use swap::state::WithdrawalKind;
+
+use crate::pda::{
+    find_pool_registry_entry,
+    find_program_config,
+    find_program_data,
+    PoolKeys,
+};
+
+// multisig approvals are passed as extra signer accounts after the declared ones
+fn build(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    approvers: &[Pubkey]
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(approvers.iter().map(|approver| AccountMeta::new_readonly(*approver, true)));
+    Instruction {
+        program_id: swap::ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
+
+fn program_config() -> Pubkey {
+    find_program_config().0
+}
+
+// pool_count and protocol_fee_recipient come from the program config account
+pub fn init_pool(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: &Pubkey,
+    pool_count: u64,
+    protocol_fee_recipient: &Pubkey,
+    token_price: u64,
+    price_denominator: u64
+) -> Instruction {
+    build(
+        swap::accounts::Initialize {
+            pool_token_account: keys.pool_token_account,
+            pool_native_account: keys.pool_native_account,
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            program_config: program_config(),
+            pool_registry_entry: find_pool_registry_entry(pool_count).0,
+            protocol_fee_recipient: *protocol_fee_recipient,
+            authority: *authority,
+            master_authority: *master_authority,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            rent: sysvar::rent::ID,
+        },
+        swap::instruction::InitInstruction {
+            token_price,
+            pool_index: keys.pool_index,
+            price_denominator,
+        },
+        &[]
+    )
+}
+
+// `master_authority` has to sign when the update sets the price guardrails, the outflow window
+// or either outflow limit, and lets a price change go past max_price_change_bps
+pub fn update_pool_params(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: Option<&Pubkey>,
+    with_roles: bool,
+    args: UpdatePoolParamsArgs
+) -> Instruction {
+    build(
+        swap::accounts::UpdatePoolParams {
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            authority: *authority,
+            master_authority: master_authority.copied(),
+            pool_roles: with_roles.then(|| keys.pool_roles()),
+        },
+        swap::instruction::UpdatePoolParamsInstruction { args },
+        &[]
+    )
+}
+
+pub fn set_pause(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    with_roles: bool,
+    flags: u8,
+    paused: bool,
+    reason: u16
+) -> Instruction {
+    build(
+        swap::accounts::SetPause {
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            authority: *authority,
+            pool_roles: with_roles.then(|| keys.pool_roles()),
+        },
+        swap::instruction::SetPauseInstruction { flags, paused, reason },
+        &[]
+    )
+}
+
+pub fn set_guardian(keys: &PoolKeys, authority: &Pubkey, guardian: Pubkey) -> Instruction {
+    build(
+        swap::accounts::SetGuardian {
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            authority: *authority,
+        },
+        swap::instruction::SetGuardianInstruction { guardian },
+        &[]
+    )
+}
+
+pub fn guardian_pause(keys: &PoolKeys, guardian: &Pubkey, flags: u8, reason: u16) -> Instruction {
+    build(
+        swap::accounts::GuardianPause {
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            guardian: *guardian,
+        },
+        swap::instruction::GuardianPauseInstruction { flags, reason },
+        &[]
+    )
+}
+
+pub fn grant_role(keys: &PoolKeys, authority: &Pubkey, member: Pubkey, roles: u8) -> Instruction {
+    build(
+        swap::accounts::GrantRole {
+            pool_roles: keys.pool_roles(),
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        swap::instruction::GrantRoleInstruction { member, roles },
+        &[]
+    )
+}
+
+pub fn revoke_role(keys: &PoolKeys, authority: &Pubkey, member: Pubkey, roles: u8) -> Instruction {
+    build(
+        swap::accounts::RevokeRole {
+            pool_roles: keys.pool_roles(),
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            authority: *authority,
+        },
+        swap::instruction::RevokeRoleInstruction { member, roles },
+        &[]
+    )
+}
+
+pub fn migrate_pool(pool_config_account: &Pubkey, authority: &Pubkey) -> Instruction {
+    build(
+        swap::accounts::MigratePool {
+            pool_config_account: *pool_config_account,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        swap::instruction::MigratePoolInstruction {},
+        &[]
+    )
+}
+
+pub fn init_program_config(
+    upgrade_authority: &Pubkey,
+    admin: Pubkey,
+    protocol_fee_recipient: Pubkey
+) -> Instruction {
+    build(
+        swap::accounts::InitProgramConfig {
+            program_config: program_config(),
+            program: swap::ID,
+            program_data: find_program_data().0,
+            upgrade_authority: *upgrade_authority,
+            system_program: system_program::ID,
+        },
+        swap::instruction::InitProgramConfigInstruction { admin, protocol_fee_recipient },
+        &[]
+    )
+}
+
+pub fn update_program_config(
+    admin: &Pubkey,
+    new_admin: Option<Pubkey>,
+    protocol_fee_recipient: Option<Pubkey>,
+    creation_fee: Option<u64>
+) -> Instruction {
+    build(
+        swap::accounts::UpdateProgramConfig {
+            program_config: program_config(),
+            admin: *admin,
+        },
+        swap::instruction::UpdateProgramConfigInstruction {
+            admin: new_admin,
+            protocol_fee_recipient,
+            creation_fee,
+        },
+        &[]
+    )
+}
+
+pub fn set_global_pause(admin: &Pubkey, paused: bool) -> Instruction {
+    build(
+        swap::accounts::UpdateProgramConfig {
+            program_config: program_config(),
+            admin: *admin,
+        },
+        swap::instruction::SetGlobalPauseInstruction { paused },
+        &[]
+    )
+}
+
+pub fn verify_pool(pool_config_account: &Pubkey, admin: &Pubkey, verified: bool) -> Instruction {
+    build(
+        swap::accounts::VerifyPool {
+            program_config: program_config(),
+            pool_config_account: *pool_config_account,
+            admin: *admin,
+        },
+        swap::instruction::VerifyPoolInstruction { verified },
+        &[]
+    )
+}
+
+pub fn add_liquid(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    depositor: &Pubkey,
+    depositor_token_account: &Pubkey,
+    with_roles: bool,
+    amount: u64
+) -> Instruction {
+    build(
+        swap::accounts::AddLiquid {
+            pool_token_account: keys.pool_token_account,
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            program_config: program_config(),
+            authority: *authority,
+            depositor_token_account: *depositor_token_account,
+            depositor: *depositor,
+            pool_roles: with_roles.then(|| keys.pool_roles()),
+            system_program: system_program::ID,
+            token_program: token::ID,
+        },
+        swap::instruction::AddLiquidInstruction { amount },
+        &[]
+    )
+}
+
+// the user's associated token account is created on the first swap
+pub fn swap_token(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    user: &Pubkey,
+    lamport_amount: u64
+) -> Instruction {
+    build(
+        swap::accounts::SwapToken {
+            pool_token_account: keys.pool_token_account,
+            pool_native_account: keys.pool_native_account,
+            pool_config_account: keys.pool_config_account,
+            user_token_account: get_associated_token_address(user, &keys.token_mint_address),
+            user_stats: keys.user_stats(user),
+            token_mint_address: keys.token_mint_address,
+            program_config: program_config(),
+            authority: *authority,
+            user: *user,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            rent: sysvar::rent::ID,
+            associated_token_program: associated_token::ID,
+        },
+        swap::instruction::SwapToken { lamport_amount },
+        &[]
+    )
+}
+
+// same accounts as swap_token, run it through simulation to read the quote
+pub fn quote_swap(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    user: &Pubkey,
+    lamport_amount: u64
+) -> Instruction {
+    build(
+        swap::accounts::QuoteSwap {
+            pool_token_account: keys.pool_token_account,
+            pool_native_account: keys.pool_native_account,
+            pool_config_account: keys.pool_config_account,
+            user_token_account: get_associated_token_address(user, &keys.token_mint_address),
+            user_stats: keys.user_stats(user),
+            token_mint_address: keys.token_mint_address,
+            program_config: program_config(),
+            authority: *authority,
+            user: *user,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            rent: sysvar::rent::ID,
+            associated_token_program: associated_token::ID,
+        },
+        swap::instruction::QuoteSwap { lamport_amount },
+        &[]
+    )
+}
+
+fn withdraw_token_accounts(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: &Pubkey,
+    destination_token_account: &Pubkey,
+    with_multisig: bool,
+    with_roles: bool
+) -> swap::accounts::WithdrawToken {
+    swap::accounts::WithdrawToken {
+        pool_token_account: keys.pool_token_account,
+        pool_config_account: keys.pool_config_account,
+        token_mint_address: keys.token_mint_address,
+        program_config: program_config(),
+        destination_token_account: *destination_token_account,
+        authority: *authority,
+        master_authority: *master_authority,
+        pool_multisig: with_multisig.then(|| keys.pool_multisig()),
+        pool_roles: with_roles.then(|| keys.pool_roles()),
+        system_program: system_program::ID,
+        token_program: token::ID,
+    }
+}
+
+fn withdraw_native_accounts(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: &Pubkey,
+    destination: &Pubkey,
+    with_multisig: bool,
+    with_roles: bool
+) -> swap::accounts::WithdrawNative {
+    swap::accounts::WithdrawNative {
+        pool_native_account: keys.pool_native_account,
+        pool_config_account: keys.pool_config_account,
+        token_mint_address: keys.token_mint_address,
+        program_config: program_config(),
+        authority: *authority,
+        master_authority: *master_authority,
+        destination: *destination,
+        pool_multisig: with_multisig.then(|| keys.pool_multisig()),
+        pool_roles: with_roles.then(|| keys.pool_roles()),
+        system_program: system_program::ID,
+    }
+}
+
+// the multisig account is only passed when the pool has approvers
+pub fn withdraw_token(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: &Pubkey,
+    destination_token_account: &Pubkey,
+    with_roles: bool,
+    approvers: &[Pubkey],
+    amount: u64
+) -> Instruction {
+    build(
+        withdraw_token_accounts(
+            keys,
+            authority,
+            master_authority,
+            destination_token_account,
+            !approvers.is_empty(),
+            with_roles
+        ),
+        swap::instruction::WithdrawTokenInstruction { amount },
+        approvers
+    )
+}
+
+pub fn drain_token(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: &Pubkey,
+    destination_token_account: &Pubkey,
+    with_roles: bool,
+    approvers: &[Pubkey]
+) -> Instruction {
+    build(
+        withdraw_token_accounts(
+            keys,
+            authority,
+            master_authority,
+            destination_token_account,
+            !approvers.is_empty(),
+            with_roles
+        ),
+        swap::instruction::DrainTokenInstruction {},
+        approvers
+    )
+}
+
+pub fn withdraw_native(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: &Pubkey,
+    destination: &Pubkey,
+    with_roles: bool,
+    approvers: &[Pubkey],
+    amount: u64
+) -> Instruction {
+    build(
+        withdraw_native_accounts(
+            keys,
+            authority,
+            master_authority,
+            destination,
+            !approvers.is_empty(),
+            with_roles
+        ),
+        swap::instruction::WithdrawNativeInstruction { amount },
+        approvers
+    )
+}
+
+pub fn drain_native(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: &Pubkey,
+    destination: &Pubkey,
+    with_roles: bool,
+    approvers: &[Pubkey]
+) -> Instruction {
+    build(
+        withdraw_native_accounts(
+            keys,
+            authority,
+            master_authority,
+            destination,
+            !approvers.is_empty(),
+            with_roles
+        ),
+        swap::instruction::DrainNativeInstruction {},
+        approvers
+    )
+}
+
+pub fn propose_authority(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    new_authority: Pubkey
+) -> Instruction {
+    build(
+        swap::accounts::ProposeAuthority {
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            authority: *authority,
+        },
+        swap::instruction::ProposeAuthorityInstruction { new_authority },
+        &[]
+    )
+}
+
+pub fn accept_authority(keys: &PoolKeys, new_authority: &Pubkey) -> Instruction {
+    build(
+        swap::accounts::AcceptAuthority {
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            new_authority: *new_authority,
+        },
+        swap::instruction::AcceptAuthorityInstruction {},
+        &[]
+    )
+}
+
+pub fn propose_master_authority(
+    keys: &PoolKeys,
+    master_authority: &Pubkey,
+    new_master_authority: Pubkey
+) -> Instruction {
+    build(
+        swap::accounts::ProposeMasterAuthority {
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            master_authority: *master_authority,
+        },
+        swap::instruction::ProposeMasterAuthorityInstruction { new_master_authority },
+        &[]
+    )
+}
+
+pub fn accept_master_authority(keys: &PoolKeys, new_master_authority: &Pubkey) -> Instruction {
+    build(
+        swap::accounts::AcceptMasterAuthority {
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            new_master_authority: *new_master_authority,
+        },
+        swap::instruction::AcceptMasterAuthorityInstruction {},
+        &[]
+    )
+}
+
+// replacing an existing multisig needs `approvers` from the current signer set
+pub fn set_multisig(
+    keys: &PoolKeys,
+    master_authority: &Pubkey,
+    approvers: &[Pubkey],
+    signers: Vec<Pubkey>,
+    threshold: u8
+) -> Instruction {
+    build(
+        swap::accounts::SetMultisig {
+            pool_multisig: keys.pool_multisig(),
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            master_authority: *master_authority,
+            system_program: system_program::ID,
+        },
+        swap::instruction::SetMultisigInstruction { signers, threshold },
+        approvers
+    )
+}
+
+pub fn set_withdrawal_delay(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: &Pubkey,
+    withdrawal_delay: i64
+) -> Instruction {
+    build(
+        swap::accounts::SetWithdrawalDelay {
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            authority: *authority,
+            master_authority: *master_authority,
+        },
+        swap::instruction::SetWithdrawalDelayInstruction { withdrawal_delay },
+        &[]
+    )
+}
+
+// `withdrawal_nonce` is the pool's current nonce, read from the config account
+#[allow(clippy::too_many_arguments)]
+pub fn queue_withdrawal(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: &Pubkey,
+    destination: &Pubkey,
+    withdrawal_nonce: u64,
+    with_roles: bool,
+    approvers: &[Pubkey],
+    kind: WithdrawalKind,
+    amount: u64
+) -> Instruction {
+    build(
+        swap::accounts::QueueWithdrawal {
+            pending_withdrawal: keys.pending_withdrawal(withdrawal_nonce),
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            program_config: program_config(),
+            authority: *authority,
+            master_authority: *master_authority,
+            destination: *destination,
+            pool_multisig: (!approvers.is_empty()).then(|| keys.pool_multisig()),
+            pool_roles: with_roles.then(|| keys.pool_roles()),
+            system_program: system_program::ID,
+        },
+        swap::instruction::QueueWithdrawalInstruction { kind, amount },
+        approvers
+    )
+}
+
+pub fn execute_withdrawal(
+    keys: &PoolKeys,
+    pending_withdrawal: &Pubkey,
+    destination: &Pubkey,
+    payer: &Pubkey
+) -> Instruction {
+    build(
+        swap::accounts::ExecuteWithdrawal {
+            pending_withdrawal: *pending_withdrawal,
+            pool_token_account: keys.pool_token_account,
+            pool_native_account: keys.pool_native_account,
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            program_config: program_config(),
+            destination: *destination,
+            payer: *payer,
+            system_program: system_program::ID,
+            token_program: token::ID,
+        },
+        swap::instruction::ExecuteWithdrawalInstruction {},
+        &[]
+    )
+}
+
+pub fn cancel_withdrawal(
+    keys: &PoolKeys,
+    pending_withdrawal: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey
+) -> Instruction {
+    build(
+        swap::accounts::CancelWithdrawal {
+            pending_withdrawal: *pending_withdrawal,
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            authority: *authority,
+            payer: *payer,
+        },
+        swap::instruction::CancelWithdrawalInstruction {},
+        &[]
+    )
+}
+
+pub fn set_withdraw_destinations(
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    master_authority: &Pubkey,
+    destinations: Vec<Pubkey>
+) -> Instruction {
+    build(
+        swap::accounts::SetWithdrawDestinations {
+            pool_config_account: keys.pool_config_account,
+            token_mint_address: keys.token_mint_address,
+            authority: *authority,
+            master_authority: *master_authority,
+        },
+        swap::instruction::SetWithdrawDestinationsInstruction { destinations },
+        &[]
+    )
+}
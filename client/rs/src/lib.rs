@@ -0,0 +1,10 @@
+pub mod accounts;
+pub mod errors;
+pub mod instructions;
+pub mod pda;
+pub mod quote;
+
+pub use accounts::*;
+pub use errors::*;
+pub use pda::*;
+pub use quote::*;
@@ -0,0 +1,178 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use swap::constants::{
+    PENDING_WITHDRAWAL_SEED,
+    POOL_CONFIG_ACCOUNT_SEED,
+    POOL_MULTISIG_SEED,
+    POOL_NATIVE_ACCOUNT_SEED,
+    POOL_REGISTRY_SEED,
+    POOL_ROLES_SEED,
+    POOL_TOKEN_ACCOUNT_SEED,
+    PROGRAM_CONFIG_SEED,
+    USER_STATS_SEED,
+};
+use swap::utils::pool_index_seed;
+
+// the pool PDAs are derived from the creator, the authority at init, not the current authority
+pub fn find_pool_config(creator: &Pubkey, mint: &Pubkey, pool_index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            POOL_CONFIG_ACCOUNT_SEED,
+            creator.as_ref(),
+            mint.as_ref(),
+            pool_index_seed(&pool_index),
+        ],
+        &swap::ID
+    )
+}
+
+pub fn find_pool_token_account(
+    creator: &Pubkey,
+    mint: &Pubkey,
+    pool_index: u64,
+    pool_config_account: &Pubkey
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            POOL_TOKEN_ACCOUNT_SEED,
+            creator.as_ref(),
+            mint.as_ref(),
+            pool_index_seed(&pool_index),
+            pool_config_account.as_ref(),
+        ],
+        &swap::ID
+    )
+}
+
+pub fn find_pool_native_account(
+    creator: &Pubkey,
+    mint: &Pubkey,
+    pool_index: u64,
+    pool_config_account: &Pubkey
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            POOL_NATIVE_ACCOUNT_SEED,
+            creator.as_ref(),
+            mint.as_ref(),
+            pool_index_seed(&pool_index),
+            pool_config_account.as_ref(),
+        ],
+        &swap::ID
+    )
+}
+
+pub fn find_pool_multisig(pool_config_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_MULTISIG_SEED, pool_config_account.as_ref()], &swap::ID)
+}
+
+pub fn find_pool_roles(pool_config_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_ROLES_SEED, pool_config_account.as_ref()], &swap::ID)
+}
+
+pub fn find_pending_withdrawal(pool_config_account: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PENDING_WITHDRAWAL_SEED, pool_config_account.as_ref(), nonce.to_le_bytes().as_ref()],
+        &swap::ID
+    )
+}
+
+pub fn find_user_stats(pool_config_account: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[USER_STATS_SEED, pool_config_account.as_ref(), user.as_ref()],
+        &swap::ID
+    )
+}
+
+pub fn find_program_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROGRAM_CONFIG_SEED], &swap::ID)
+}
+
+pub fn find_pool_registry_entry(index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_REGISTRY_SEED, index.to_le_bytes().as_ref()], &swap::ID)
+}
+
+pub fn find_program_data() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[swap::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+// every address of one pool, derived once and shared by the instruction builders
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolKeys {
+    pub creator: Pubkey,
+    pub token_mint_address: Pubkey,
+    pub pool_index: u64,
+    pub pool_config_account: Pubkey,
+    pub pool_token_account: Pubkey,
+    pub pool_native_account: Pubkey,
+}
+
+impl PoolKeys {
+    pub fn new(creator: Pubkey, token_mint_address: Pubkey, pool_index: u64) -> Self {
+        let (pool_config_account, _) = find_pool_config(&creator, &token_mint_address, pool_index);
+        let (pool_token_account, _) = find_pool_token_account(
+            &creator,
+            &token_mint_address,
+            pool_index,
+            &pool_config_account
+        );
+        let (pool_native_account, _) = find_pool_native_account(
+            &creator,
+            &token_mint_address,
+            pool_index,
+            &pool_config_account
+        );
+        Self {
+            creator,
+            token_mint_address,
+            pool_index,
+            pool_config_account,
+            pool_token_account,
+            pool_native_account,
+        }
+    }
+
+    pub fn from_config(pool_config_account: Pubkey, config: &swap::state::PoolConfigAccount) -> Self {
+        Self {
+            creator: config.creator,
+            token_mint_address: config.token_mint_address,
+            pool_index: config.pool_index,
+            pool_config_account,
+            pool_token_account: config.pool_token_account,
+            pool_native_account: config.pool_native_account,
+        }
+    }
+
+    pub fn pool_multisig(&self) -> Pubkey {
+        find_pool_multisig(&self.pool_config_account).0
+    }
+
+    pub fn pool_roles(&self) -> Pubkey {
+        find_pool_roles(&self.pool_config_account).0
+    }
+
+    pub fn pending_withdrawal(&self, nonce: u64) -> Pubkey {
+        find_pending_withdrawal(&self.pool_config_account, nonce).0
+    }
+
+    pub fn user_stats(&self, user: &Pubkey) -> Pubkey {
+        find_user_stats(&self.pool_config_account, user).0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_index_zero_keeps_unindexed_addresses() {
+        let creator = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let unindexed = Pubkey::find_program_address(
+            &[POOL_CONFIG_ACCOUNT_SEED, creator.as_ref(), mint.as_ref()],
+            &swap::ID
+        );
+        assert_eq!(find_pool_config(&creator, &mint, 0), unindexed);
+        assert_ne!(find_pool_config(&creator, &mint, 1), unindexed);
+    }
+}
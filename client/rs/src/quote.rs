@@ -0,0 +1,76 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{ AccountDeserialize, AnchorDeserialize };
+use anchor_spl::token::TokenAccount;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::message::Message;
+use solana_sdk::transaction::Transaction;
+use swap::instructions::SwapQuoteResult;
+use swap::quote::{ quote_swap, SwapQuote };
+use swap::state::PoolConfigAccount;
+
+use crate::accounts::{ fetch_pool_config, ClientError };
+use crate::errors::decode_transaction_error;
+use crate::instructions;
+use crate::pda::PoolKeys;
+
+// same math the program runs in swap_token, against a config and reserve the caller already has
+pub fn quote_with_reserve(
+    config: &PoolConfigAccount,
+    token_reserve: u64,
+    lamports_in: u64
+) -> Result<SwapQuote, ClientError> {
+    quote_swap(
+        config.token_price,
+        config.price_denominator(),
+        config.fee_bps,
+        token_reserve,
+        lamports_in
+    ).map_err(ClientError::Quote)
+}
+
+pub fn fetch_token_reserve(client: &RpcClient, config: &PoolConfigAccount) -> Result<u64, ClientError> {
+    let account = client.get_account(&config.pool_token_account)?;
+    let token_account = TokenAccount::try_deserialize(&mut account.data.as_slice()).map_err(|err| {
+        ClientError::Decode(config.pool_token_account, Box::new(err))
+    })?;
+    Ok(token_account.amount)
+}
+
+pub fn fetch_swap_quote(
+    client: &RpcClient,
+    pool_config_account: &Pubkey,
+    lamports_in: u64
+) -> Result<SwapQuote, ClientError> {
+    let config = fetch_pool_config(client, pool_config_account)?;
+    let token_reserve = fetch_token_reserve(client, &config)?;
+    quote_with_reserve(&config, token_reserve, lamports_in)
+}
+
+// runs the on-chain quote_swap in simulation, nothing is signed or sent
+pub fn simulate_swap_quote(
+    client: &RpcClient,
+    keys: &PoolKeys,
+    authority: &Pubkey,
+    user: &Pubkey,
+    lamport_amount: u64
+) -> Result<SwapQuoteResult, ClientError> {
+    let instruction = instructions::quote_swap(keys, authority, user, lamport_amount);
+    let transaction = Transaction::new_unsigned(Message::new(&[instruction], Some(user)));
+    let result = client.simulate_transaction_with_config(&transaction, RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        ..RpcSimulateTransactionConfig::default()
+    })?.value;
+    if let Some(err) = result.err {
+        let logs = result.logs.unwrap_or_default();
+        return Err(match decode_transaction_error(&err, &logs) {
+            Some(program_error) => ClientError::Program(Box::new(program_error)),
+            None => ClientError::Simulation(err.to_string()),
+        });
+    }
+    let (data, _) = result.return_data
+        .ok_or_else(|| ClientError::Simulation("no return data".to_string()))?.data;
+    let data = base64::decode(data).map_err(|err| ClientError::Simulation(err.to_string()))?;
+    SwapQuoteResult::try_from_slice(&data).map_err(|err| ClientError::Simulation(err.to_string()))
+}
@@ -14,8 +14,13 @@ import { setup, delay } from "./setup";
 
   const addLiquidAmount = 1000;
   const decimals = 6;
-  const { program, mintAddress, poolConfigAccount, poolTokenAccount } =
-    setup(authority);
+  const {
+    program,
+    mintAddress,
+    poolConfigAccount,
+    poolTokenAccount,
+    programConfig,
+  } = setup(authority);
   const depositorTokenAccount = await getAssociatedTokenAddress(
     mintAddress,
     authority.publicKey
@@ -32,6 +37,8 @@ import { setup, delay } from "./setup";
       depositor: authority.publicKey, // reuse authority as a depositor to liquid pool
       tokenProgram: TOKEN_PROGRAM_ID,
       systemProgram: anchor.web3.SystemProgram.programId,
+      poolRoles: null,
+      programConfig: programConfig,
     })
     .rpc();
 
@@ -19,10 +19,18 @@ import { setup, delay } from "./setup";
     poolConfigAccount,
     poolNativeAccount,
     poolTokenAccount,
+    programConfig,
   } = setup(authority);
   const rawAmount = parseUnits(TOKEN_PRICE.toString(), decimals).toNumber();
+  // every new pool takes the next slot in the registry
+  const { poolCount, protocolFeeRecipient } =
+    await program.account.programConfig.fetch(programConfig);
+  const poolRegistryEntry = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("pool_registry_seed"), poolCount.toArrayLike(Buffer, "le", 8)],
+    program.programId
+  )[0];
   const signature = await program.methods
-    .initInstruction(new anchor.BN(rawAmount))
+    .initInstruction(new anchor.BN(rawAmount), new anchor.BN(0), new anchor.BN(1))
     .accounts({
       poolConfigAccount: poolConfigAccount,
       poolNativeAccount: poolNativeAccount,
@@ -33,6 +41,9 @@ import { setup, delay } from "./setup";
       tokenProgram: TOKEN_PROGRAM_ID,
       systemProgram: anchor.web3.SystemProgram.programId,
       rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      programConfig: programConfig,
+      poolRegistryEntry: poolRegistryEntry,
+      protocolFeeRecipient: protocolFeeRecipient,
     })
     .rpc();
 
@@ -52,12 +52,18 @@ export function setup(authority: anchor.web3.Keypair) {
     program.programId
   )[0];
 
+  const programConfig = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("program_config_seed")],
+    program.programId
+  )[0];
+
   return {
     program,
     mintAddress,
     poolConfigAccount,
     poolNativeAccount,
     poolTokenAccount,
+    programConfig,
   };
 }
 
@@ -16,6 +16,7 @@ import { setup, delay } from "./setup";
     poolConfigAccount,
     poolNativeAccount,
     poolTokenAccount,
+    programConfig,
   } = setup(authority);
 
   const userTokenAccount = await getAssociatedTokenAddress(
@@ -23,6 +24,15 @@ import { setup, delay } from "./setup";
     user.publicKey
   );
 
+  const userStats = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("user_stats_seed"),
+      poolConfigAccount.toBuffer(),
+      user.publicKey.toBuffer(),
+    ],
+    program.programId
+  )[0];
+
   const swapSolValue = 0.5;
   const signature = await program.methods
     .swapToken(new anchor.BN(swapSolValue * anchor.web3.LAMPORTS_PER_SOL))
@@ -36,6 +46,8 @@ import { setup, delay } from "./setup";
       user: user.publicKey,
       tokenProgram: TOKEN_PROGRAM_ID,
       systemProgram: anchor.web3.SystemProgram.programId,
+      userStats: userStats,
+      programConfig: programConfig,
     })
     .signers([user])
     .rpc();
@@ -1,3 +1,34 @@
 pub const POOL_CONFIG_ACCOUNT_SEED: &[u8] = b"pool_config_account_seed";
 pub const POOL_TOKEN_ACCOUNT_SEED: &[u8] = b"pool_token_account_seed";
-pub const POOL_NATIVE_ACCOUNT_SEED: &[u8] = b"pool_native_account_seed";
\ No newline at end of file
+pub const POOL_NATIVE_ACCOUNT_SEED: &[u8] = b"pool_native_account_seed";
+pub const POOL_MULTISIG_SEED: &[u8] = b"pool_multisig_seed";
+pub const PENDING_WITHDRAWAL_SEED: &[u8] = b"pending_withdrawal_seed";
+pub const POOL_ROLES_SEED: &[u8] = b"pool_roles_seed";
+pub const USER_STATS_SEED: &[u8] = b"user_stats_seed";
+pub const PROGRAM_CONFIG_SEED: &[u8] = b"program_config_seed";
+pub const POOL_REGISTRY_SEED: &[u8] = b"pool_registry_seed";
+
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
+pub const MAX_WITHDRAW_DESTINATIONS: usize = 4;
+pub const MAX_ROLE_MEMBERS: usize = 16;
+pub const MAX_WITHDRAWAL_DELAY: i64 = 30 * 24 * 60 * 60; // seconds
+pub const MAX_OUTFLOW_WINDOW: i64 = 365 * 24 * 60 * 60; // seconds
+pub const MAX_PRICE_UPDATE_COOLDOWN: i64 = 30 * 24 * 60 * 60; // seconds
+pub const MAX_FEE_BPS: u16 = 1_000;
+pub const POOL_CONFIG_VERSION: u8 = 2;
+pub const POOL_CONFIG_RESERVED_BYTES: usize = 95; // PoolConfigAccount reserved + reserved_tail
+pub const MAX_MINT_DECIMALS: u8 = 12;
+pub const MAX_TOKENS_PER_SOL: u64 = 1_000_000_000; // whole tokens, bounds token_price at init
+pub const LEGACY_POOL_CONFIG_LEN: usize = 180; // unversioned layout before migrate_pool
+pub const FEE_BPS_DENOMINATOR: u64 = 10_000;
+
+pub const PAUSE_SWAP: u8 = 1 << 0;
+pub const PAUSE_DEPOSIT: u8 = 1 << 1;
+pub const PAUSE_WITHDRAW: u8 = 1 << 2;
+pub const PAUSE_ALL: u8 = PAUSE_SWAP | PAUSE_DEPOSIT | PAUSE_WITHDRAW;
+
+pub const ROLE_PRICE_SETTER: u8 = 1 << 0;
+pub const ROLE_PAUSER: u8 = 1 << 1;
+pub const ROLE_LIQUIDITY_MANAGER: u8 = 1 << 2;
+pub const ROLE_TREASURER: u8 = 1 << 3;
+pub const ROLE_ALL: u8 = ROLE_PRICE_SETTER | ROLE_PAUSER | ROLE_LIQUIDITY_MANAGER | ROLE_TREASURER;
@@ -1,3 +1,4 @@
+use crate::quote::QuoteError;
 use anchor_lang::prelude::*;
 
 #[error_code]
@@ -16,4 +17,199 @@ pub enum CustomError {
 
     #[msg("without withdraw permission")]
     WithdrawPermission,
-}
\ No newline at end of file
+
+    #[msg("native vault must stay rent exempt")]
+    NativeVaultRentExempt,
+
+    #[msg("signer is not the pending authority")]
+    InvalidPendingAuthority,
+
+    #[msg("Invalid multisig config")]
+    InvalidMultisig,
+
+    #[msg("not enough multisig approvals")]
+    MultisigThresholdNotMet,
+
+    #[msg("withdrawals are timelocked, queue the withdrawal")]
+    WithdrawalTimelocked,
+
+    #[msg("queued withdrawal is still locked")]
+    WithdrawalLocked,
+
+    #[msg("Invalid withdrawal delay")]
+    InvalidWithdrawalDelay,
+
+    #[msg("Invalid withdraw destination")]
+    InvalidDestination,
+
+    #[msg("Invalid amount")]
+    InvalidAmount,
+
+    #[msg("withdrawal exceeds the pool outflow limit")]
+    OutflowLimitExceeded,
+
+    #[msg("Invalid outflow limit")]
+    InvalidOutflowLimit,
+
+    #[msg("Invalid token price")]
+    InvalidPrice,
+
+    #[msg("Invalid fee")]
+    InvalidFee,
+
+    #[msg("master authority signature required")]
+    MasterSignatureRequired,
+
+    #[msg("math overflow")]
+    MathOverflow,
+
+    #[msg("price was updated too recently")]
+    PriceUpdateCooldown,
+
+    #[msg("price change exceeds the limit without master authority")]
+    PriceChangeTooLarge,
+
+    #[msg("Invalid price guardrail")]
+    InvalidPriceGuardrail,
+
+    #[msg("deposits are paused")]
+    DepositPaused,
+
+    #[msg("withdrawals are paused")]
+    WithdrawPaused,
+
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+
+    #[msg("Invalid guardian")]
+    InvalidGuardian,
+
+    #[msg("Invalid role")]
+    InvalidRole,
+
+    #[msg("Role members full")]
+    RoleMembersFull,
+
+    #[msg("Pool already migrated")]
+    PoolAlreadyMigrated,
+
+    #[msg("Program is paused")]
+    ProgramPaused,
+
+    #[msg("Invalid program admin")]
+    InvalidProgramAdmin,
+
+    #[msg("Invalid upgrade authority")]
+    InvalidUpgradeAuthority,
+
+    #[msg("Invalid mint decimals")]
+    InvalidMintDecimals,
+
+    #[msg("user insufficient lamports")]
+    InsufficientUserLamports,
+
+    #[msg("pool insufficient tokens")]
+    InsufficientPoolTokens,
+
+    #[msg("pool insufficient lamports")]
+    InsufficientPoolLamports,
+
+    #[msg("Amount too small to swap for any tokens")]
+    AmountTooSmall,
+}
+
+impl From<QuoteError> for CustomError {
+    fn from(err: QuoteError) -> Self {
+        match err {
+            QuoteError::MathOverflow => CustomError::MathOverflow,
+            QuoteError::InvalidPrice => CustomError::InvalidPrice,
+            QuoteError::AmountTooSmall => CustomError::AmountTooSmall,
+        }
+    }
+}
+
+impl CustomError {
+    // in code order, append a new variant here and name it in the assertion below
+    pub const ALL: &'static [CustomError] = &[
+        CustomError::InsufficientFunds,
+        CustomError::InvalidAccount,
+        CustomError::DeactivatePool,
+        CustomError::InvalidAuthority,
+        CustomError::WithdrawPermission,
+        CustomError::NativeVaultRentExempt,
+        CustomError::InvalidPendingAuthority,
+        CustomError::InvalidMultisig,
+        CustomError::MultisigThresholdNotMet,
+        CustomError::WithdrawalTimelocked,
+        CustomError::WithdrawalLocked,
+        CustomError::InvalidWithdrawalDelay,
+        CustomError::InvalidDestination,
+        CustomError::InvalidAmount,
+        CustomError::OutflowLimitExceeded,
+        CustomError::InvalidOutflowLimit,
+        CustomError::InvalidPrice,
+        CustomError::InvalidFee,
+        CustomError::MasterSignatureRequired,
+        CustomError::MathOverflow,
+        CustomError::PriceUpdateCooldown,
+        CustomError::PriceChangeTooLarge,
+        CustomError::InvalidPriceGuardrail,
+        CustomError::DepositPaused,
+        CustomError::WithdrawPaused,
+        CustomError::InvalidPauseFlags,
+        CustomError::InvalidGuardian,
+        CustomError::InvalidRole,
+        CustomError::RoleMembersFull,
+        CustomError::PoolAlreadyMigrated,
+        CustomError::ProgramPaused,
+        CustomError::InvalidProgramAdmin,
+        CustomError::InvalidUpgradeAuthority,
+        CustomError::InvalidMintDecimals,
+        CustomError::InsufficientUserLamports,
+        CustomError::InsufficientPoolTokens,
+        CustomError::InsufficientPoolLamports,
+        CustomError::AmountTooSmall,
+    ];
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        let index = code.checked_sub(anchor_lang::error::ERROR_CODE_OFFSET)?;
+        Self::ALL.get(index as usize).copied()
+    }
+}
+
+// checked at build time: each listed variant sits at its own code and the list ends at the last variant
+const _: () = {
+    let mut index = 0;
+    while index < CustomError::ALL.len() {
+        assert!(CustomError::ALL[index] as usize == index);
+        index += 1;
+    }
+    assert!(CustomError::ALL.len() == (CustomError::AmountTooSmall as usize) + 1);
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_stable() {
+        for (index, error) in CustomError::ALL.iter().enumerate() {
+            assert_eq!(u32::from(*error), anchor_lang::error::ERROR_CODE_OFFSET + (index as u32));
+        }
+        assert_eq!(u32::from(CustomError::InsufficientFunds), 6000);
+        assert_eq!(u32::from(CustomError::InvalidMintDecimals), 6033);
+        assert_eq!(u32::from(CustomError::InsufficientPoolTokens), 6035);
+        assert_eq!(u32::from(CustomError::InsufficientPoolLamports), 6036);
+        assert_eq!(u32::from(CustomError::AmountTooSmall), 6037);
+    }
+
+    #[test]
+    fn decodes_codes() {
+        assert_eq!(
+            CustomError::from_code(6034).map(|error| error.name()),
+            Some("InsufficientUserLamports".to_string())
+        );
+        assert!(CustomError::from_code(2003).is_none());
+        assert!(CustomError::from_code(6000 + (CustomError::ALL.len() as u32)).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{ PoolParams, WithdrawalKind };
+
+#[event]
+pub struct PoolInitialized {
+    pub pool_config_account: Pubkey,
+    pub token_mint_address: Pubkey,
+    pub pool_index: u64,
+    pub pool_token_account: Pubkey,
+    pub pool_native_account: Pubkey,
+    pub authority: Pubkey,
+    pub master_authority: Pubkey,
+    pub token_price: u64,
+    pub price_denominator: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub pool_config_account: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub pool_token_balance: u64, // after the deposit
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensSwapped {
+    pub pool_config_account: Pubkey,
+    pub user: Pubkey,
+    pub lamports_in: u64,
+    pub fee_lamports: u64,
+    pub tokens_out: u64,
+    pub token_price: u64,
+    pub price_denominator: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub pool_config_account: Pubkey,
+    pub authority: Pubkey,
+    pub before: PoolParams,
+    pub after: PoolParams,
+    pub timestamp: i64,
+}
+
+// `authority` is the signer, or the queueing key for an executed pending withdrawal
+#[event]
+pub struct TokenWithdrawn {
+    pub pool_config_account: Pubkey,
+    pub authority: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NativeWithdrawn {
+    pub pool_config_account: Pubkey,
+    pub authority: Pubkey,
+    pub destination: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolDrained {
+    pub pool_config_account: Pubkey,
+    pub authority: Pubkey,
+    pub kind: WithdrawalKind,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+// `pause_flags` is the full set after the change, `authority` may be the guardian
+#[event]
+pub struct PauseUpdated {
+    pub pool_config_account: Pubkey,
+    pub authority: Pubkey,
+    pub pause_flags: u8,
+    pub pause_reason: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GuardianUpdated {
+    pub pool_config_account: Pubkey,
+    pub authority: Pubkey,
+    pub guardian: Pubkey,
+    pub timestamp: i64,
+}
+
+// `roles` is what the member holds after the change, 0 once fully revoked
+#[event]
+pub struct RoleUpdated {
+    pub pool_config_account: Pubkey,
+    pub authority: Pubkey,
+    pub member: Pubkey,
+    pub roles: u8,
+    pub timestamp: i64,
+}
+
+// `master` tells the master authority apart from the pool authority,
+// proposing Pubkey::default() cancels the transfer
+#[event]
+pub struct AuthorityProposed {
+    pub pool_config_account: Pubkey,
+    pub master: bool,
+    pub current: Pubkey,
+    pub proposed: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub pool_config_account: Pubkey,
+    pub master: bool,
+    pub previous: Pubkey,
+    pub new: Pubkey,
+    pub timestamp: i64,
+}
+
+// `multisig` is Pubkey::default() once disabled
+#[event]
+pub struct MultisigUpdated {
+    pub pool_config_account: Pubkey,
+    pub master_authority: Pubkey,
+    pub multisig: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalDelayUpdated {
+    pub pool_config_account: Pubkey,
+    pub authority: Pubkey,
+    pub withdrawal_delay: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalQueued {
+    pub pool_config_account: Pubkey,
+    pub pending_withdrawal: Pubkey,
+    pub nonce: u64,
+    pub master_authority: Pubkey,
+    pub kind: WithdrawalKind,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub unlock_at: i64,
+    pub timestamp: i64,
+}
+
+// followed by the TokenWithdrawn or NativeWithdrawn event for the transfer itself
+#[event]
+pub struct WithdrawalExecuted {
+    pub pool_config_account: Pubkey,
+    pub pending_withdrawal: Pubkey,
+    pub nonce: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalCancelled {
+    pub pool_config_account: Pubkey,
+    pub pending_withdrawal: Pubkey,
+    pub nonce: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+// an empty list lifts the restriction
+#[event]
+pub struct WithdrawDestinationsUpdated {
+    pub pool_config_account: Pubkey,
+    pub authority: Pubkey,
+    pub destinations: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolMigrated {
+    pub pool_config_account: Pubkey,
+    pub authority: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolVerified {
+    pub pool_config_account: Pubkey,
+    pub admin: Pubkey,
+    pub verified: bool,
+    pub timestamp: i64,
+}
+
+// `authority` is the upgrade authority on creation and the admin afterwards
+#[event]
+pub struct ProgramConfigUpdated {
+    pub authority: Pubkey,
+    pub admin: Pubkey,
+    pub protocol_fee_recipient: Pubkey,
+    pub creation_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GlobalPauseUpdated {
+    pub admin: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
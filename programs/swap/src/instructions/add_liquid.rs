@@ -2,10 +2,15 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{ Mint, Token, TokenAccount };
 
 use crate::constants::{
+    PAUSE_DEPOSIT,
     POOL_CONFIG_ACCOUNT_SEED,
+    POOL_ROLES_SEED,
     POOL_TOKEN_ACCOUNT_SEED,
+    ROLE_LIQUIDITY_MANAGER,
+    PROGRAM_CONFIG_SEED,
 };
 use crate::error::CustomError;
+use crate::events::*;
 use crate::state::*;
 use crate::utils::*;
 
@@ -16,8 +21,9 @@ pub struct AddLiquid<'info> {
         mut,
         seeds = [
             POOL_TOKEN_ACCOUNT_SEED,
-            authority.key().as_ref(),
+            pool_config_account.creator.as_ref(),
             token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
             pool_config_account.key().as_ref(),
         ],
         bump = pool_config_account.pool_token_account_bump,
@@ -27,24 +33,41 @@ pub struct AddLiquid<'info> {
     pub pool_token_account: Account<'info, TokenAccount>,
 
     #[account(
+        mut,
         seeds = [
             POOL_CONFIG_ACCOUNT_SEED,
-            authority.key().as_ref(),
+            pool_config_account.creator.as_ref(),
             token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
         ],
-        bump = pool_config_account.pool_config_account_bump
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = authority @ CustomError::InvalidAuthority,
+        constraint = !pool_config_account.is_paused(PAUSE_DEPOSIT) @ CustomError::DepositPaused
     )]
     pub pool_config_account: Account<'info, PoolConfigAccount>,
     pub token_mint_address: Account<'info, Mint>,
-    /// CHECK: this account use to verify escrow_token_account and config_account seed
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ CustomError::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    /// CHECK: this account use to verify the pool authority
     pub authority: AccountInfo<'info>,
     #[account(mut,
     token::mint=token_mint_address,
     token::authority = depositor,
     constraint = depositor_token_account.amount >= amount @ CustomError::InsufficientFunds)]
     pub depositor_token_account: Account<'info, TokenAccount>,
-    #[account(mut, constraint = depositor.lamports() > 0 && depositor.data_is_empty() @ CustomError::InvalidAccount)]
+    #[account(mut,
+    constraint = depositor.lamports() > 0 && depositor.data_is_empty() @ CustomError::InvalidAccount,
+    constraint = pool_config_account.is_authorized(&depositor.key(), pool_roles.as_deref(), ROLE_LIQUIDITY_MANAGER) @ CustomError::InvalidAuthority)]
     pub depositor: Signer<'info>,
+    #[account(
+        seeds = [POOL_ROLES_SEED, pool_config_account.key().as_ref()],
+        bump = pool_roles.bump
+    )]
+    pub pool_roles: Option<Account<'info, PoolRoles>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
 }
@@ -61,5 +84,16 @@ pub fn handler_add_liquid<'info>(
         ctx.accounts.token_program.to_account_info(),
         None
     )?;
+
+    ctx.accounts.pool_token_account.reload()?;
+    let config_account = &mut ctx.accounts.pool_config_account;
+    config_account.total_liquidity_added = config_account.total_liquidity_added.saturating_add(amount);
+    emit!(LiquidityAdded {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amount,
+        pool_token_balance: ctx.accounts.pool_token_account.amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
\ No newline at end of file
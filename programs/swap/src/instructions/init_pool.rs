@@ -2,15 +2,21 @@ use crate::constants::{
     POOL_TOKEN_ACCOUNT_SEED,
     POOL_CONFIG_ACCOUNT_SEED,
     POOL_NATIVE_ACCOUNT_SEED,
+    POOL_CONFIG_VERSION,
+    MAX_MINT_DECIMALS,
+    POOL_REGISTRY_SEED,
+    PROGRAM_CONFIG_SEED,
 };
 use crate::state::*;
 use crate::error::*;
+use crate::events::*;
+use crate::utils::{ check_pool_price, pool_index_seed, transfer_native_to_account };
 
 use anchor_lang::prelude::*;
 use anchor_spl::token::{ Mint, Token, TokenAccount };
 
 #[derive(Accounts)]
-#[instruction()]
+#[instruction(token_price: u64, pool_index: u64)]
 pub struct Initialize<'info> {
     #[account(
         init,
@@ -19,6 +25,7 @@ pub struct Initialize<'info> {
             POOL_TOKEN_ACCOUNT_SEED,
             authority.key().as_ref(),
             token_mint_address.key().as_ref(),
+            pool_index_seed(&pool_index),
             pool_config_account.key().as_ref(),
         ],
         bump,
@@ -33,6 +40,7 @@ pub struct Initialize<'info> {
             POOL_NATIVE_ACCOUNT_SEED,
             authority.key().as_ref(),
             token_mint_address.key().as_ref(),
+            pool_index_seed(&pool_index),
             pool_config_account.key().as_ref()
         ],
         bump
@@ -46,6 +54,7 @@ pub struct Initialize<'info> {
             POOL_CONFIG_ACCOUNT_SEED,
             authority.key().as_ref(),
             token_mint_address.key().as_ref(),
+            pool_index_seed(&pool_index),
         ],
         bump,
         space = PoolConfigAccount::LEN
@@ -53,6 +62,24 @@ pub struct Initialize<'info> {
     pub pool_config_account: Account<'info, PoolConfigAccount>,
 
     pub token_mint_address: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ CustomError::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [POOL_REGISTRY_SEED, program_config.pool_count.to_le_bytes().as_ref()],
+        bump,
+        space = PoolRegistryEntry::LEN
+    )]
+    pub pool_registry_entry: Box<Account<'info, PoolRegistryEntry>>,
+    /// CHECK: receives the creation fee
+    #[account(mut, address = program_config.protocol_fee_recipient @ CustomError::InvalidAccount)]
+    pub protocol_fee_recipient: AccountInfo<'info>,
     #[account(mut, constraint = authority.data_is_empty() @ CustomError::InvalidAccount)]
     pub authority: Signer<'info>,
     /// CHECK: this account use to setup pool config account
@@ -65,10 +92,17 @@ pub struct Initialize<'info> {
 
 pub fn handler_init<'info>(
     ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
-    token_price: u64
+    token_price: u64,
+    pool_index: u64,
+    price_denominator: u64
 ) -> Result<()> {
+    ctx.accounts.check_pool_params(token_price, price_denominator)?;
+    ctx.accounts.pay_creation_fee()?;
+
     let pool_config_account = &mut ctx.accounts.pool_config_account;
     pool_config_account.token_price = token_price;
+    pool_config_account.price_denominator = price_denominator;
+    pool_config_account.pool_index = pool_index;
     pool_config_account.pool_config_account_bump = *ctx.bumps.get("pool_config_account").unwrap();
     pool_config_account.pool_token_account_bump = *ctx.bumps.get("pool_token_account").unwrap();
     let pool_native_account_bump = *ctx.bumps.get("pool_native_account").unwrap();
@@ -78,14 +112,60 @@ pub fn handler_init<'info>(
     pool_config_account.pool_native_account = ctx.accounts.pool_native_account.key();
     pool_config_account.master_authority = ctx.accounts.master_authority.key();
     pool_config_account.authority = ctx.accounts.authority.key();
-    pool_config_account.is_active = true;
+    pool_config_account.creator = ctx.accounts.authority.key();
+    pool_config_account.version = POOL_CONFIG_VERSION;
+
+    ctx.accounts.create_native_account_vault(pool_native_account_bump, pool_index)?;
 
-    ctx.accounts.create_native_account_vault(pool_native_account_bump)?;
+    let now = Clock::get()?.unix_timestamp;
+    let program_config = &mut ctx.accounts.program_config;
+    let pool_registry_entry = &mut ctx.accounts.pool_registry_entry;
+    pool_registry_entry.bump = *ctx.bumps.get("pool_registry_entry").unwrap();
+    pool_registry_entry.index = program_config.pool_count;
+    pool_registry_entry.pool_config_account = ctx.accounts.pool_config_account.key();
+    pool_registry_entry.token_mint_address = ctx.accounts.token_mint_address.key();
+    pool_registry_entry.creator = ctx.accounts.authority.key();
+    pool_registry_entry.created_at = now;
+    program_config.pool_count += 1;
+
+    emit!(PoolInitialized {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        token_mint_address: ctx.accounts.token_mint_address.key(),
+        pool_index,
+        pool_token_account: ctx.accounts.pool_token_account.key(),
+        pool_native_account: ctx.accounts.pool_native_account.key(),
+        authority: ctx.accounts.authority.key(),
+        master_authority: ctx.accounts.master_authority.key(),
+        token_price,
+        price_denominator,
+        timestamp: now,
+    });
     Ok(())
 }
 
 impl<'info> Initialize<'info> {
-    fn create_native_account_vault(&self, pool_native_account_bump: u8) -> Result<()> {
+    // anyone can create a pool, so reject mints and prices no frontend could list
+    fn check_pool_params(&self, token_price: u64, price_denominator: u64) -> Result<()> {
+        let decimals = self.token_mint_address.decimals;
+        require_gte!(MAX_MINT_DECIMALS, decimals, CustomError::InvalidMintDecimals);
+        check_pool_price(token_price, price_denominator, decimals)
+    }
+
+    fn pay_creation_fee(&self) -> Result<()> {
+        let creation_fee = self.program_config.creation_fee;
+        if creation_fee == 0 {
+            return Ok(());
+        }
+        transfer_native_to_account(
+            self.authority.to_account_info(),
+            self.protocol_fee_recipient.to_account_info(),
+            creation_fee,
+            self.system_program.to_account_info(),
+            None
+        )
+    }
+
+    fn create_native_account_vault(&self, pool_native_account_bump: u8, pool_index: u64) -> Result<()> {
         let authority_key = self.authority.key();
         let mint_address = self.token_mint_address.key();
         let pool_config_account_key = self.pool_config_account.key();
@@ -94,6 +174,7 @@ impl<'info> Initialize<'info> {
                 POOL_NATIVE_ACCOUNT_SEED,
                 authority_key.as_ref(),
                 mint_address.as_ref(),
+                pool_index_seed(&pool_index),
                 pool_config_account_key.as_ref(),
                 bytemuck::bytes_of(&pool_native_account_bump),
             ][..],
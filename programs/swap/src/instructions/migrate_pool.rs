@@ -0,0 +1,77 @@
+use crate::constants::{ LEGACY_POOL_CONFIG_LEN, PAUSE_ALL, POOL_CONFIG_VERSION };
+
+use crate::state::*;
+use crate::error::*;
+use crate::events::*;
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    /// CHECK: may still hold an older layout that Account<PoolConfigAccount> can't load, checked in the handler
+    #[account(mut, owner = crate::ID @ CustomError::InvalidAccount)]
+    pub pool_config_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// grows the account to PoolConfigAccount::LEN and fills defaults for the fields its version lacks,
+// the unversioned layout shares its prefix with the current one up to `authority`
+pub fn handler_migrate_pool<'info>(
+    ctx: Context<'_, '_, '_, 'info, MigratePool<'info>>
+) -> Result<()> {
+    let config_info = ctx.accounts.pool_config_account.to_account_info();
+    let legacy = config_info.data_len() == LEGACY_POOL_CONFIG_LEN;
+
+    if config_info.data_len() < PoolConfigAccount::LEN {
+        let rent_exempt = Rent::get()?.minimum_balance(PoolConfigAccount::LEN);
+        let top_up = rent_exempt.saturating_sub(config_info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: config_info.clone(),
+                    }
+                ),
+                top_up
+            )?;
+        }
+        config_info.realloc(PoolConfigAccount::LEN, true)?;
+    }
+
+    let mut config_account = PoolConfigAccount::try_deserialize(
+        &mut &config_info.try_borrow_data()?[..]
+    )?;
+    require_keys_eq!(
+        config_account.authority,
+        ctx.accounts.authority.key(),
+        CustomError::InvalidAuthority
+    );
+    require_gt!(POOL_CONFIG_VERSION, config_account.version, CustomError::PoolAlreadyMigrated);
+    let from_version = config_account.version;
+
+    if legacy {
+        // is_active sat where pause_flags is now
+        config_account.pause_flags = if config_account.pause_flags == 1 { 0 } else { PAUSE_ALL };
+        config_account.creator = config_account.authority;
+    }
+    if config_account.version < 2 {
+        // prices were base units per whole SOL before the denominator
+        config_account.price_denominator = 1;
+    }
+    config_account.version = POOL_CONFIG_VERSION;
+    config_account.try_serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+
+    emit!(PoolMigrated {
+        pool_config_account: config_info.key(),
+        authority: ctx.accounts.authority.key(),
+        from_version,
+        to_version: POOL_CONFIG_VERSION,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
@@ -1,13 +1,31 @@
 pub mod init_pool;
 pub mod add_liquid;
 pub mod swap_token;
+pub mod quote_swap;
 pub mod withdraw_token_pool;
 pub mod withdraw_native_pool;
 pub mod update_pool_config;
+pub mod transfer_authority;
+pub mod set_multisig;
+pub mod withdrawal_queue;
+pub mod withdraw_destinations;
+pub mod pause_pool;
+pub mod pool_roles;
+pub mod migrate_pool;
+pub mod program_config;
 
 pub use init_pool::*;
 pub use add_liquid::*;
 pub use swap_token::*;
+pub use quote_swap::*;
 pub use withdraw_token_pool::*;
 pub use withdraw_native_pool::*;
-pub use update_pool_config::*;
\ No newline at end of file
+pub use update_pool_config::*;
+pub use transfer_authority::*;
+pub use set_multisig::*;
+pub use withdrawal_queue::*;
+pub use withdraw_destinations::*;
+pub use pause_pool::*;
+pub use pool_roles::*;
+pub use migrate_pool::*;
+pub use program_config::*;
\ No newline at end of file
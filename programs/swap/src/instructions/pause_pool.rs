@@ -0,0 +1,130 @@
+use crate::constants::{ POOL_CONFIG_ACCOUNT_SEED, POOL_ROLES_SEED, PAUSE_ALL, ROLE_PAUSER };
+
+use crate::state::*;
+use crate::error::*;
+use crate::events::*;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        constraint = pool_config_account.is_authorized(&authority.key(), pool_roles.as_deref(), ROLE_PAUSER) @ CustomError::InvalidAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [POOL_ROLES_SEED, pool_config_account.key().as_ref()],
+        bump = pool_roles.bump
+    )]
+    pub pool_roles: Option<Account<'info, PoolRoles>>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = authority @ CustomError::InvalidAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = guardian @ CustomError::InvalidGuardian
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub guardian: Signer<'info>,
+}
+
+// pauses or resumes only the PAUSE_* bits in `flags`, the others keep their state
+pub fn handler_set_pause<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetPause<'info>>,
+    flags: u8,
+    paused: bool,
+    reason: u16
+) -> Result<()> {
+    require!(flags != 0 && flags & !PAUSE_ALL == 0, CustomError::InvalidPauseFlags);
+    let config_account = &mut ctx.accounts.pool_config_account;
+    if paused {
+        config_account.pause_flags |= flags;
+    } else {
+        config_account.pause_flags &= !flags;
+    }
+    config_account.pause_reason = reason;
+
+    emit!(PauseUpdated {
+        pool_config_account: config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        pause_flags: config_account.pause_flags,
+        pause_reason: reason,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// Pubkey::default() removes the guardian
+pub fn handler_set_guardian<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetGuardian<'info>>,
+    guardian: Pubkey
+) -> Result<()> {
+    ctx.accounts.pool_config_account.guardian = guardian;
+
+    emit!(GuardianUpdated {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        guardian,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// the guardian can only add pause bits, resuming stays with the authority
+pub fn handler_guardian_pause<'info>(
+    ctx: Context<'_, '_, '_, 'info, GuardianPause<'info>>,
+    flags: u8,
+    reason: u16
+) -> Result<()> {
+    require!(flags != 0 && flags & !PAUSE_ALL == 0, CustomError::InvalidPauseFlags);
+    let config_account = &mut ctx.accounts.pool_config_account;
+    config_account.pause_flags |= flags;
+    config_account.pause_reason = reason;
+
+    emit!(PauseUpdated {
+        pool_config_account: config_account.key(),
+        authority: ctx.accounts.guardian.key(),
+        pause_flags: config_account.pause_flags,
+        pause_reason: reason,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use crate::constants::{ POOL_CONFIG_ACCOUNT_SEED, POOL_ROLES_SEED, MAX_ROLE_MEMBERS, ROLE_ALL };
+
+use crate::state::*;
+use crate::error::*;
+use crate::events::*;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct GrantRole<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [POOL_ROLES_SEED, pool_config_account.key().as_ref()],
+        bump,
+        space = PoolRoles::LEN
+    )]
+    pub pool_roles: Account<'info, PoolRoles>,
+
+    #[account(
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = authority @ CustomError::InvalidAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_ROLES_SEED, pool_config_account.key().as_ref()],
+        bump = pool_roles.bump
+    )]
+    pub pool_roles: Account<'info, PoolRoles>,
+
+    #[account(
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = authority @ CustomError::InvalidAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+}
+
+// every role is a subset of the authority's own permissions, so the authority alone grants them
+pub fn handler_grant_role<'info>(
+    ctx: Context<'_, '_, '_, 'info, GrantRole<'info>>,
+    member: Pubkey,
+    roles: u8
+) -> Result<()> {
+    require!(roles != 0 && roles & !ROLE_ALL == 0, CustomError::InvalidRole);
+    require_keys_neq!(member, Pubkey::default(), CustomError::InvalidRole);
+
+    let pool_roles = &mut ctx.accounts.pool_roles;
+    pool_roles.bump = *ctx.bumps.get("pool_roles").unwrap();
+    pool_roles.pool_config_account = ctx.accounts.pool_config_account.key();
+    let member_roles = match pool_roles.members.iter_mut().find(|entry| entry.member == member) {
+        Some(entry) => {
+            entry.roles |= roles;
+            entry.roles
+        }
+        None => {
+            require_gt!(MAX_ROLE_MEMBERS, pool_roles.members.len(), CustomError::RoleMembersFull);
+            pool_roles.members.push(RoleMember { member, roles });
+            roles
+        }
+    };
+
+    emit!(RoleUpdated {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        member,
+        roles: member_roles,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// members left without any role are dropped from the list
+pub fn handler_revoke_role<'info>(
+    ctx: Context<'_, '_, '_, 'info, RevokeRole<'info>>,
+    member: Pubkey,
+    roles: u8
+) -> Result<()> {
+    require!(roles != 0 && roles & !ROLE_ALL == 0, CustomError::InvalidRole);
+
+    let pool_roles = &mut ctx.accounts.pool_roles;
+    let mut member_roles = 0;
+    for entry in pool_roles.members.iter_mut().filter(|entry| entry.member == member) {
+        entry.roles &= !roles;
+        member_roles = entry.roles;
+    }
+    pool_roles.members.retain(|entry| entry.roles != 0);
+
+    emit!(RoleUpdated {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        member,
+        roles: member_roles,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use crate::constants::PROGRAM_CONFIG_SEED;
+use crate::program::Swap;
+
+use crate::state::*;
+use crate::error::*;
+use crate::events::*;
+
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct InitProgramConfig<'info> {
+    #[account(
+        init,
+        payer = upgrade_authority,
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump,
+        space = ProgramConfig::LEN
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ CustomError::InvalidAccount)]
+    pub program: Program<'info, Swap>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ CustomError::InvalidUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyPool<'info> {
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        has_one = admin @ CustomError::InvalidProgramAdmin
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(mut)]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramConfig<'info> {
+    #[account(
+        mut,
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        has_one = admin @ CustomError::InvalidProgramAdmin
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub admin: Signer<'info>,
+}
+
+// only the upgrade authority can create the singleton, it then hands control to `admin`
+pub fn handler_init_program_config<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitProgramConfig<'info>>,
+    admin: Pubkey,
+    protocol_fee_recipient: Pubkey
+) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.bump = *ctx.bumps.get("program_config").unwrap();
+    program_config.admin = admin;
+    program_config.protocol_fee_recipient = protocol_fee_recipient;
+
+    emit!(ProgramConfigUpdated {
+        authority: ctx.accounts.upgrade_authority.key(),
+        admin,
+        protocol_fee_recipient,
+        creation_fee: program_config.creation_fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// unset fields keep their current value
+pub fn handler_update_program_config<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateProgramConfig<'info>>,
+    admin: Option<Pubkey>,
+    protocol_fee_recipient: Option<Pubkey>,
+    creation_fee: Option<u64>
+) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+    if let Some(admin) = admin {
+        require_keys_neq!(admin, Pubkey::default(), CustomError::InvalidProgramAdmin);
+        program_config.admin = admin;
+    }
+    if let Some(protocol_fee_recipient) = protocol_fee_recipient {
+        program_config.protocol_fee_recipient = protocol_fee_recipient;
+    }
+    if let Some(creation_fee) = creation_fee {
+        program_config.creation_fee = creation_fee;
+    }
+
+    emit!(ProgramConfigUpdated {
+        authority: ctx.accounts.admin.key(),
+        admin: program_config.admin,
+        protocol_fee_recipient: program_config.protocol_fee_recipient,
+        creation_fee: program_config.creation_fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+// halts pool creation and every instruction that moves pool funds: deposits, swaps,
+// quotes, direct withdrawals and queueing or executing pending ones. admin instructions
+// stay open so pool keys can still pause pools, rotate authorities and roles, tighten
+// limits, cancel pending withdrawals and migrate while the program is halted
+pub fn handler_set_global_pause<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateProgramConfig<'info>>,
+    paused: bool
+) -> Result<()> {
+    ctx.accounts.program_config.paused = paused;
+
+    emit!(GlobalPauseUpdated {
+        admin: ctx.accounts.admin.key(),
+        paused,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn handler_verify_pool<'info>(
+    ctx: Context<'_, '_, '_, 'info, VerifyPool<'info>>,
+    verified: bool
+) -> Result<()> {
+    ctx.accounts.pool_config_account.is_verified = verified;
+
+    emit!(PoolVerified {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        admin: ctx.accounts.admin.key(),
+        verified,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
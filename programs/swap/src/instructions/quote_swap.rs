@@ -0,0 +1,115 @@
+use crate::constants::{
+    POOL_TOKEN_ACCOUNT_SEED,
+    POOL_CONFIG_ACCOUNT_SEED,
+    POOL_NATIVE_ACCOUNT_SEED,
+    PROGRAM_CONFIG_SEED,
+};
+use crate::state::*;
+use crate::error::*;
+use crate::quote::SwapQuote;
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{ Mint, Token, TokenAccount };
+
+// same accounts as SwapToken so a wallet can reuse its account list, none of them written
+#[derive(Accounts)]
+pub struct QuoteSwap<'info> {
+    #[account(
+        seeds = [
+            POOL_TOKEN_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+            pool_config_account.key().as_ref(),
+        ],
+        bump = pool_config_account.pool_token_account_bump,
+        token::mint = token_mint_address,
+        token::authority = pool_config_account
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: This account will be create when create swap pool
+    #[account(
+        seeds=[
+            POOL_NATIVE_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+            pool_config_account.key().as_ref()
+        ],
+        bump = pool_config_account.pool_native_account_bump
+    )]
+    pub pool_native_account: AccountInfo<'info>,
+
+    #[account(
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = authority @ CustomError::InvalidAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    /// CHECK: the user's token account, it may not exist before the first swap
+    pub user_token_account: UncheckedAccount<'info>,
+    /// CHECK: the user's stats account, it may not exist before the first swap
+    pub user_stats: UncheckedAccount<'info>,
+    pub token_mint_address: Account<'info, Mint>,
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ CustomError::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    /// CHECK: the pool authority co-signs swaps but not quotes
+    pub authority: UncheckedAccount<'info>,
+    /// CHECK: the user the quote is for, no signature needed to read
+    pub user: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuoteResult {
+    pub lamports_in: u64,
+    pub fee_lamports: u64,
+    pub tokens_out: u64,
+    pub price_impact_bps: u16,
+    pub token_price: u64,
+    pub price_denominator: u64,
+    pub effective_price: u64, // token base units per SOL after the fee
+}
+
+impl SwapQuoteResult {
+    pub fn new(quote: SwapQuote, token_price: u64, price_denominator: u64) -> Self {
+        Self {
+            lamports_in: quote.lamports_in,
+            fee_lamports: quote.fee_lamports,
+            tokens_out: quote.tokens_out,
+            price_impact_bps: quote.price_impact_bps,
+            token_price,
+            price_denominator,
+            effective_price: quote.effective_price(),
+        }
+    }
+}
+
+pub fn handler_quote_swap<'info>(
+    ctx: Context<'_, '_, '_, 'info, QuoteSwap<'info>>,
+    lamport_amount: u64
+) -> Result<SwapQuoteResult> {
+    let pool_config_account = &ctx.accounts.pool_config_account;
+    let quote = pool_config_account.swap_quote(ctx.accounts.pool_token_account.amount, lamport_amount)?;
+    Ok(
+        SwapQuoteResult::new(
+            quote,
+            pool_config_account.token_price,
+            pool_config_account.price_denominator()
+        )
+    )
+}
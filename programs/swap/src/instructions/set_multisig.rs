@@ -0,0 +1,83 @@
+use crate::constants::{ POOL_CONFIG_ACCOUNT_SEED, POOL_MULTISIG_SEED, MAX_MULTISIG_SIGNERS };
+
+use crate::state::*;
+use crate::error::*;
+use crate::events::*;
+use crate::utils::*;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct SetMultisig<'info> {
+    #[account(
+        init_if_needed,
+        payer = master_authority,
+        seeds = [POOL_MULTISIG_SEED, pool_config_account.key().as_ref()],
+        bump,
+        space = PoolMultisig::LEN
+    )]
+    pub pool_multisig: Account<'info, PoolMultisig>,
+
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = master_authority @ CustomError::WithdrawPermission
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    #[account(mut)]
+    pub master_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// empty signers with a zero threshold turns the multisig off,
+// once enabled every change needs the current threshold of approvals
+pub fn handler_set_multisig<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetMultisig<'info>>,
+    signers: Vec<Pubkey>,
+    threshold: u8
+) -> Result<()> {
+    check_multisig_approval(
+        ctx.accounts.pool_config_account.multisig,
+        Some(&ctx.accounts.pool_multisig),
+        ctx.remaining_accounts
+    )?;
+
+    let disable = signers.is_empty() && threshold == 0;
+    if !disable {
+        require_gte!(MAX_MULTISIG_SIGNERS, signers.len(), CustomError::InvalidMultisig);
+        require!(
+            threshold > 0 && (threshold as usize) <= signers.len(),
+            CustomError::InvalidMultisig
+        );
+        for (index, signer) in signers.iter().enumerate() {
+            require!(!signers[..index].contains(signer), CustomError::InvalidMultisig);
+        }
+    }
+
+    let pool_multisig = &mut ctx.accounts.pool_multisig;
+    pool_multisig.bump = *ctx.bumps.get("pool_multisig").unwrap();
+    pool_multisig.pool_config_account = ctx.accounts.pool_config_account.key();
+    pool_multisig.threshold = threshold;
+    pool_multisig.signers = signers.clone();
+
+    let multisig = if disable { Pubkey::default() } else { ctx.accounts.pool_multisig.key() };
+    ctx.accounts.pool_config_account.multisig = multisig;
+
+    emit!(MultisigUpdated {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        master_authority: ctx.accounts.master_authority.key(),
+        multisig,
+        signers,
+        threshold,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
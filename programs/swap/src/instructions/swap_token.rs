@@ -2,13 +2,15 @@ use crate::constants::{
     POOL_TOKEN_ACCOUNT_SEED,
     POOL_CONFIG_ACCOUNT_SEED,
     POOL_NATIVE_ACCOUNT_SEED,
+    USER_STATS_SEED,
+    PROGRAM_CONFIG_SEED,
 };
 use crate::state::*;
 use crate::error::*;
+use crate::events::*;
 use crate::utils::*;
 
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{ Mint, Token, TokenAccount };
 
@@ -19,14 +21,14 @@ pub struct SwapToken<'info> {
         mut,
         seeds = [
             POOL_TOKEN_ACCOUNT_SEED,
-            authority.key().as_ref(),
+            pool_config_account.creator.as_ref(),
             token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
             pool_config_account.key().as_ref(),
         ],
         bump = pool_config_account.pool_token_account_bump,
         token::mint = token_mint_address,
-        token::authority = pool_config_account,
-        constraint = pool_config_account.is_active @ CustomError::DeactivatePool
+        token::authority = pool_config_account
     )]
     pub pool_token_account: Account<'info, TokenAccount>,
 
@@ -34,8 +36,9 @@ pub struct SwapToken<'info> {
     #[account(mut,
         seeds=[
             POOL_NATIVE_ACCOUNT_SEED,
-            authority.key().as_ref(),
+            pool_config_account.creator.as_ref(),
             token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
             pool_config_account.key().as_ref()
         ],
         bump = pool_config_account.pool_native_account_bump
@@ -45,10 +48,12 @@ pub struct SwapToken<'info> {
     #[account(mut,
         seeds = [
             POOL_CONFIG_ACCOUNT_SEED,
-            authority.key().as_ref(),
+            pool_config_account.creator.as_ref(),
             token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
         ],
-        bump = pool_config_account.pool_config_account_bump
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = authority @ CustomError::InvalidAuthority
     )]
     pub pool_config_account: Account<'info, PoolConfigAccount>,
     #[account(
@@ -58,10 +63,24 @@ pub struct SwapToken<'info> {
         payer = user
     )]
     pub user_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [USER_STATS_SEED, pool_config_account.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = UserStats::LEN
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
     pub token_mint_address: Account<'info, Mint>,
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ CustomError::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
     #[account(mut, constraint = authority.data_is_empty() @ CustomError::InvalidAccount)]
     pub authority: Signer<'info>,
-    #[account(mut, constraint = user.lamports() > lamport_amount @ CustomError::InsufficientFunds)]
+    #[account(mut, constraint = user.lamports() > lamport_amount @ CustomError::InsufficientUserLamports)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
@@ -73,10 +92,38 @@ pub fn handler_swap_token<'info>(
     ctx: Context<'_, '_, '_, 'info, SwapToken<'info>>,
     lamport_amount: u64
 ) -> Result<()> {
-    let token_price = ctx.accounts.pool_config_account.token_price;
-    let token_amount = (token_price * lamport_amount) / LAMPORTS_PER_SOL;
+    let quote = ctx.accounts.pool_config_account.swap_quote(
+        ctx.accounts.pool_token_account.amount,
+        lamport_amount
+    )?;
+    let token_amount = quote.tokens_out;
     ctx.accounts.transfer_sol(lamport_amount)?;
     ctx.accounts.transfer_token(token_amount)?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let user_stats = &mut ctx.accounts.user_stats;
+    let new_buyer = user_stats.swap_count == 0;
+    if new_buyer {
+        user_stats.bump = *ctx.bumps.get("user_stats").unwrap();
+        user_stats.pool_config_account = ctx.accounts.pool_config_account.key();
+        user_stats.user = ctx.accounts.user.key();
+    }
+    user_stats.swap_count = user_stats.swap_count.saturating_add(1);
+    user_stats.lamports_in = user_stats.lamports_in.saturating_add(lamport_amount);
+    user_stats.tokens_out = user_stats.tokens_out.saturating_add(token_amount);
+    user_stats.last_swap_at = now;
+    ctx.accounts.pool_config_account.record_swap(lamport_amount, token_amount, new_buyer, now);
+
+    emit!(TokensSwapped {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        user: ctx.accounts.user.key(),
+        lamports_in: lamport_amount,
+        fee_lamports: quote.fee_lamports,
+        tokens_out: token_amount,
+        token_price: ctx.accounts.pool_config_account.token_price,
+        price_denominator: ctx.accounts.pool_config_account.price_denominator(),
+        timestamp: now,
+    });
     Ok(())
 }
 
@@ -93,14 +140,16 @@ impl<'info> SwapToken<'info> {
     }
 
     fn transfer_token(&self, token_amount: u64) -> Result<()> {
-        let authority = self.authority.key();
+        let creator = self.pool_config_account.creator;
         let mint = self.token_mint_address.key();
+        let pool_index = self.pool_config_account.pool_index;
         let pool_config_account_bump = self.pool_config_account.pool_config_account_bump;
         let seeds = &[
             &[
                 POOL_CONFIG_ACCOUNT_SEED,
-                authority.as_ref(),
+                creator.as_ref(),
                 mint.as_ref(),
+                pool_index_seed(&pool_index),
                 bytemuck::bytes_of(&pool_config_account_bump),
             ][..],
         ];
@@ -0,0 +1,149 @@
+use crate::constants::POOL_CONFIG_ACCOUNT_SEED;
+
+use crate::state::*;
+use crate::error::*;
+use crate::events::*;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = authority @ CustomError::InvalidAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        constraint = pool_config_account.pending_authority == new_authority.key() @ CustomError::InvalidPendingAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeMasterAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = master_authority @ CustomError::WithdrawPermission
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub master_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptMasterAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        constraint = pool_config_account.pending_master_authority == new_master_authority.key() @ CustomError::InvalidPendingAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub new_master_authority: Signer<'info>,
+}
+
+// proposing Pubkey::default() cancels a pending transfer
+pub fn handler_propose_authority<'info>(
+    ctx: Context<'_, '_, '_, 'info, ProposeAuthority<'info>>,
+    new_authority: Pubkey
+) -> Result<()> {
+    ctx.accounts.pool_config_account.pending_authority = new_authority;
+
+    emit!(AuthorityProposed {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        master: false,
+        current: ctx.accounts.authority.key(),
+        proposed: new_authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn handler_accept_authority<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptAuthority<'info>>
+) -> Result<()> {
+    let config_account = &mut ctx.accounts.pool_config_account;
+    let previous = config_account.authority;
+    config_account.authority = ctx.accounts.new_authority.key();
+    config_account.pending_authority = Pubkey::default();
+
+    emit!(AuthorityTransferred {
+        pool_config_account: config_account.key(),
+        master: false,
+        previous,
+        new: config_account.authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn handler_propose_master_authority<'info>(
+    ctx: Context<'_, '_, '_, 'info, ProposeMasterAuthority<'info>>,
+    new_master_authority: Pubkey
+) -> Result<()> {
+    ctx.accounts.pool_config_account.pending_master_authority = new_master_authority;
+
+    emit!(AuthorityProposed {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        master: true,
+        current: ctx.accounts.master_authority.key(),
+        proposed: new_master_authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn handler_accept_master_authority<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcceptMasterAuthority<'info>>
+) -> Result<()> {
+    let config_account = &mut ctx.accounts.pool_config_account;
+    let previous = config_account.master_authority;
+    config_account.master_authority = ctx.accounts.new_master_authority.key();
+    config_account.pending_master_authority = Pubkey::default();
+
+    emit!(AuthorityTransferred {
+        pool_config_account: config_account.key(),
+        master: true,
+        previous,
+        new: config_account.master_authority,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
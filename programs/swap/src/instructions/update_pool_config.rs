@@ -1,40 +1,154 @@
-use crate::constants::POOL_CONFIG_ACCOUNT_SEED;
+use crate::constants::{
+    POOL_CONFIG_ACCOUNT_SEED,
+    POOL_ROLES_SEED,
+    MAX_FEE_BPS,
+    MAX_OUTFLOW_WINDOW,
+    MAX_PRICE_UPDATE_COOLDOWN,
+    FEE_BPS_DENOMINATOR,
+    ROLE_PRICE_SETTER,
+};
 
+use crate::events::*;
 use crate::state::*;
 use crate::error::*;
+use crate::utils::check_pool_price;
 
 use anchor_lang::prelude::*;
-use anchor_spl::token::{ Mint, Token };
+use anchor_spl::token::Mint;
+
+// unset fields keep their current value
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct UpdatePoolParamsArgs {
+    pub token_price: Option<u64>,
+    pub price_denominator: Option<u64>,
+    pub fee_bps: Option<u16>,
+    pub outflow_window: Option<i64>,
+    pub token_outflow_limit: Option<u64>,
+    pub native_outflow_limit: Option<u64>,
+    pub max_price_change_bps: Option<u16>,
+    pub price_update_cooldown: Option<i64>,
+}
 
 #[derive(Accounts)]
-#[instruction()]
-pub struct UpdateConfig<'info> {
+pub struct UpdatePoolParams<'info> {
     #[account(
         mut,
         seeds = [
             POOL_CONFIG_ACCOUNT_SEED,
-            authority.key().as_ref(),
+            pool_config_account.creator.as_ref(),
             token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
         ],
         bump = pool_config_account.pool_config_account_bump,
-        has_one = authority @ CustomError::InvalidAuthority
+        constraint = pool_config_account.is_authorized(&authority.key(), pool_roles.as_deref(), ROLE_PRICE_SETTER) @ CustomError::InvalidAuthority
     )]
     pub pool_config_account: Account<'info, PoolConfigAccount>,
 
     pub token_mint_address: Account<'info, Mint>,
     #[account(mut, constraint = authority.data_is_empty() @ CustomError::InvalidAccount)]
     pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
+    // co-signs changes that loosen treasury protections
+    #[account(address = pool_config_account.master_authority @ CustomError::WithdrawPermission)]
+    pub master_authority: Option<Signer<'info>>,
+    #[account(
+        seeds = [POOL_ROLES_SEED, pool_config_account.key().as_ref()],
+        bump = pool_roles.bump
+    )]
+    pub pool_roles: Option<Account<'info, PoolRoles>>,
 }
 
-pub fn handler_update_config<'info>(
-    ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
-    disable: bool
+pub fn handler_update_pool_params<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdatePoolParams<'info>>,
+    args: UpdatePoolParamsArgs
 ) -> Result<()> {
+    let master_signed = ctx.accounts.master_authority.is_some();
+    let decimals = ctx.accounts.token_mint_address.decimals;
+    let now = Clock::get()?.unix_timestamp;
     let config_account = &mut ctx.accounts.pool_config_account;
-    config_account.is_active = disable;
+    let before = config_account.params();
+
+    let guardrail_updated =
+        args.max_price_change_bps.is_some() || args.price_update_cooldown.is_some();
+    if guardrail_updated {
+        require!(master_signed, CustomError::MasterSignatureRequired);
+        if let Some(max_price_change_bps) = args.max_price_change_bps {
+            require_gte!(
+                FEE_BPS_DENOMINATOR,
+                max_price_change_bps as u64,
+                CustomError::InvalidPriceGuardrail
+            );
+            config_account.max_price_change_bps = max_price_change_bps;
+        }
+        if let Some(price_update_cooldown) = args.price_update_cooldown {
+            require!(
+                (0..=MAX_PRICE_UPDATE_COOLDOWN).contains(&price_update_cooldown),
+                CustomError::InvalidPriceGuardrail
+            );
+            config_account.price_update_cooldown = price_update_cooldown;
+        }
+    }
+
+    if args.token_price.is_some() || args.price_denominator.is_some() {
+        let token_price = args.token_price.unwrap_or(config_account.token_price);
+        let price_denominator = args.price_denominator.unwrap_or(config_account.price_denominator());
+        check_pool_price(token_price, price_denominator, decimals)?;
+        let next_price_update_at = config_account.last_price_update_at
+            .checked_add(config_account.price_update_cooldown)
+            .ok_or(CustomError::MathOverflow)?;
+        require_gte!(now, next_price_update_at, CustomError::PriceUpdateCooldown);
+        let max_price_change_bps = config_account.max_price_change_bps;
+        if max_price_change_bps > 0 && !master_signed {
+            require_gte!(
+                max_price_change_bps as u128,
+                config_account.price_change_bps(token_price, price_denominator),
+                CustomError::PriceChangeTooLarge
+            );
+        }
+        // the admin verified the old price, a new one needs verifying again
+        if token_price != config_account.token_price ||
+            price_denominator != config_account.price_denominator()
+        {
+            config_account.is_verified = false;
+        }
+        config_account.token_price = token_price;
+        config_account.price_denominator = price_denominator;
+        config_account.last_price_update_at = now;
+    }
+    if let Some(fee_bps) = args.fee_bps {
+        require_gte!(MAX_FEE_BPS, fee_bps, CustomError::InvalidFee);
+        config_account.fee_bps = fee_bps;
+    }
+
+    let outflow_updated =
+        args.outflow_window.is_some() ||
+        args.token_outflow_limit.is_some() ||
+        args.native_outflow_limit.is_some();
+    if outflow_updated {
+        require!(master_signed, CustomError::MasterSignatureRequired);
+        if let Some(outflow_window) = args.outflow_window {
+            require!(
+                (0..=MAX_OUTFLOW_WINDOW).contains(&outflow_window),
+                CustomError::InvalidOutflowLimit
+            );
+            if outflow_window != config_account.outflow_window {
+                config_account.outflow_window = outflow_window;
+                config_account.restart_outflow_window(now);
+            }
+        }
+        if let Some(token_outflow_limit) = args.token_outflow_limit {
+            config_account.token_outflow_limit = token_outflow_limit;
+        }
+        if let Some(native_outflow_limit) = args.native_outflow_limit {
+            config_account.native_outflow_limit = native_outflow_limit;
+        }
+    }
+
+    emit!(ConfigUpdated {
+        pool_config_account: config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        before,
+        after: config_account.params(),
+        timestamp: now,
+    });
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,52 @@
+use crate::constants::{ POOL_CONFIG_ACCOUNT_SEED, MAX_WITHDRAW_DESTINATIONS };
+
+use crate::state::*;
+use crate::error::*;
+use crate::events::*;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[derive(Accounts)]
+pub struct SetWithdrawDestinations<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = master_authority @ CustomError::WithdrawPermission,
+        has_one = authority @ CustomError::InvalidAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+    pub master_authority: Signer<'info>,
+}
+
+// an empty list lifts the restriction, both keys sign so the master key
+// alone can't redirect withdrawals
+pub fn handler_set_withdraw_destinations<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetWithdrawDestinations<'info>>,
+    destinations: Vec<Pubkey>
+) -> Result<()> {
+    require_gte!(MAX_WITHDRAW_DESTINATIONS, destinations.len(), CustomError::InvalidDestination);
+    require!(
+        !destinations.contains(&Pubkey::default()),
+        CustomError::InvalidDestination
+    );
+    let mut withdraw_destinations = [Pubkey::default(); MAX_WITHDRAW_DESTINATIONS];
+    withdraw_destinations[..destinations.len()].copy_from_slice(&destinations);
+    ctx.accounts.pool_config_account.withdraw_destinations = withdraw_destinations;
+
+    emit!(WithdrawDestinationsUpdated {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        destinations,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
@@ -1,9 +1,17 @@
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{ Mint, Token };
+use anchor_spl::token::Mint;
 
-use crate::constants::{ POOL_CONFIG_ACCOUNT_SEED, POOL_NATIVE_ACCOUNT_SEED };
+use crate::constants::{
+    PAUSE_WITHDRAW,
+    POOL_CONFIG_ACCOUNT_SEED,
+    POOL_MULTISIG_SEED,
+    POOL_ROLES_SEED,
+    POOL_NATIVE_ACCOUNT_SEED,
+    ROLE_TREASURER,
+    PROGRAM_CONFIG_SEED,
+};
 use crate::error::CustomError;
+use crate::events::*;
 use crate::state::*;
 use crate::utils::*;
 
@@ -13,8 +21,9 @@ pub struct WithdrawNative<'info> {
     #[account(mut,
         seeds=[
             POOL_NATIVE_ACCOUNT_SEED,
-            authority.key().as_ref(),
+            pool_config_account.creator.as_ref(),
             token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
             pool_config_account.key().as_ref()
         ],
         bump = pool_config_account.pool_native_account_bump
@@ -24,64 +33,110 @@ pub struct WithdrawNative<'info> {
     #[account(mut,
         seeds = [
             POOL_CONFIG_ACCOUNT_SEED,
-            authority.key().as_ref(),
+            pool_config_account.creator.as_ref(),
             token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
         ],
         bump = pool_config_account.pool_config_account_bump,
         has_one = master_authority  @ CustomError::WithdrawPermission,
-        has_one = authority @ CustomError::InvalidAuthority
+        constraint = pool_config_account.withdrawal_delay == 0 @ CustomError::WithdrawalTimelocked,
+        constraint = !pool_config_account.is_paused(PAUSE_WITHDRAW) @ CustomError::WithdrawPaused,
+        constraint = pool_config_account.is_authorized(&authority.key(), pool_roles.as_deref(), ROLE_TREASURER) @ CustomError::InvalidAuthority,
     )]
     pub pool_config_account: Account<'info, PoolConfigAccount>,
     // CHECK: this mint use to validate account
     pub token_mint_address: Account<'info, Mint>,
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ CustomError::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
     #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(mut)]
     pub master_authority: Signer<'info>,
+    /// CHECK: any system account, restricted by the pool withdraw destinations when set
+    #[account(
+        mut,
+        constraint = pool_config_account.is_allowed_destination(destination.key) @ CustomError::InvalidDestination
+    )]
+    pub destination: AccountInfo<'info>,
+    #[account(
+        seeds = [POOL_MULTISIG_SEED, pool_config_account.key().as_ref()],
+        bump = pool_multisig.bump
+    )]
+    pub pool_multisig: Option<Account<'info, PoolMultisig>>,
+    #[account(
+        seeds = [POOL_ROLES_SEED, pool_config_account.key().as_ref()],
+        bump = pool_roles.bump
+    )]
+    pub pool_roles: Option<Account<'info, PoolRoles>>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 pub fn handler_withdraw_native<'info>(
     ctx: Context<'_, '_, '_, 'info, WithdrawNative<'info>>,
     lamports: u64
 ) -> Result<()> {
-    let lamports_balance = **ctx.accounts.pool_native_account.lamports.borrow_mut();
-
-    require_gte!(lamports_balance, lamports, CustomError::InsufficientFunds);
-
-    ctx.accounts.withdraw_native(lamports)?;
+    ctx.accounts.withdraw_native(lamports, ctx.remaining_accounts)?;
 
+    emit!(NativeWithdrawn {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        destination: ctx.accounts.destination.key(),
+        lamports,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
 
 pub fn handler_drain_native<'info>(
     ctx: Context<'_, '_, '_, 'info, WithdrawNative<'info>>
 ) -> Result<()> {
-    let lamports = **ctx.accounts.pool_native_account.lamports.borrow_mut();
-    ctx.accounts.withdraw_native(lamports)?;
+    let lamports = native_vault_withdrawable(&ctx.accounts.pool_native_account)?;
+    ctx.accounts.withdraw_native(lamports, ctx.remaining_accounts)?;
+    emit!(PoolDrained {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        kind: WithdrawalKind::Native,
+        destination: ctx.accounts.destination.key(),
+        amount: lamports,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
 
 impl<'info> WithdrawNative<'info> {
-    fn withdraw_native(&self, lamports: u64) -> Result<()> {
-        let authority = self.authority.key();
+    fn withdraw_native(&mut self, lamports: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        check_multisig_approval(
+            self.pool_config_account.multisig,
+            self.pool_multisig.as_ref(),
+            remaining_accounts
+        )?;
+        check_native_vault_withdraw(&self.pool_native_account, lamports)?;
+        self.pool_config_account.record_outflow(
+            WithdrawalKind::Native,
+            lamports,
+            Clock::get()?.unix_timestamp
+        )?;
+        let creator = self.pool_config_account.creator;
         let mint = self.token_mint_address.key();
+        let pool_index = self.pool_config_account.pool_index;
         let pool_config_account = self.pool_config_account.key();
         let pool_native_account_bump = self.pool_config_account.pool_native_account_bump;
         let seeds = &[
             &[
                 POOL_NATIVE_ACCOUNT_SEED,
-                authority.as_ref(),
+                creator.as_ref(),
                 mint.as_ref(),
+                pool_index_seed(&pool_index),
                 pool_config_account.as_ref(),
                 bytemuck::bytes_of(&pool_native_account_bump),
             ][..],
         ];
         transfer_native_to_account(
             self.pool_native_account.to_account_info(),
-            self.master_authority.to_account_info(),
+            self.destination.to_account_info(),
             lamports,
             self.system_program.to_account_info(),
             Some(seeds)
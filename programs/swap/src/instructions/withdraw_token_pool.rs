@@ -1,9 +1,17 @@
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{ Mint, Token, TokenAccount };
 
-use crate::constants::{ POOL_CONFIG_ACCOUNT_SEED, POOL_TOKEN_ACCOUNT_SEED };
+use crate::constants::{
+    PAUSE_WITHDRAW,
+    POOL_CONFIG_ACCOUNT_SEED,
+    POOL_MULTISIG_SEED,
+    POOL_ROLES_SEED,
+    POOL_TOKEN_ACCOUNT_SEED,
+    ROLE_TREASURER,
+    PROGRAM_CONFIG_SEED,
+};
 use crate::error::CustomError;
+use crate::events::*;
 use crate::state::*;
 use crate::utils::*;
 
@@ -13,8 +21,9 @@ pub struct WithdrawToken<'info> {
         mut,
         seeds = [
             POOL_TOKEN_ACCOUNT_SEED,
-            authority.key().as_ref(),
+            pool_config_account.creator.as_ref(),
             token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
             pool_config_account.key().as_ref(),
         ],
         bump = pool_config_account.pool_token_account_bump,
@@ -24,32 +33,49 @@ pub struct WithdrawToken<'info> {
     pub pool_token_account: Box<Account<'info, TokenAccount>>,
 
     #[account(
+        mut,
         seeds = [
             POOL_CONFIG_ACCOUNT_SEED,
-            authority.key().as_ref(),
+            pool_config_account.creator.as_ref(),
             token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
         ],
         bump = pool_config_account.pool_config_account_bump,
         has_one = master_authority @ CustomError::WithdrawPermission,
-        has_one = authority @ CustomError::InvalidAuthority,
+        constraint = pool_config_account.withdrawal_delay == 0 @ CustomError::WithdrawalTimelocked,
+        constraint = !pool_config_account.is_paused(PAUSE_WITHDRAW) @ CustomError::WithdrawPaused,
+        constraint = pool_config_account.is_authorized(&authority.key(), pool_roles.as_deref(), ROLE_TREASURER) @ CustomError::InvalidAuthority,
         has_one = pool_token_account
     )]
     pub pool_config_account: Box<Account<'info, PoolConfigAccount>>,
     pub token_mint_address: Account<'info, Mint>,
     #[account(
-        init_if_needed,
-        associated_token::mint = token_mint_address,
-        associated_token::authority = master_authority,
-        payer = master_authority
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ CustomError::ProgramPaused
     )]
-    pub master_authority_token_account: Box<Account<'info, TokenAccount>>,
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        token::mint = token_mint_address,
+        constraint = pool_config_account.is_allowed_destination(&destination_token_account.owner) @ CustomError::InvalidDestination
+    )]
+    pub destination_token_account: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(mut)]
     pub master_authority: Signer<'info>,
+    #[account(
+        seeds = [POOL_MULTISIG_SEED, pool_config_account.key().as_ref()],
+        bump = pool_multisig.bump
+    )]
+    pub pool_multisig: Option<Account<'info, PoolMultisig>>,
+    #[account(
+        seeds = [POOL_ROLES_SEED, pool_config_account.key().as_ref()],
+        bump = pool_roles.bump
+    )]
+    pub pool_roles: Option<Account<'info, PoolRoles>>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 pub fn handler_withdraw_token<'info>(
@@ -57,9 +83,16 @@ pub fn handler_withdraw_token<'info>(
     amount: u64
 ) -> Result<()> {
     //
-    require_gte!(ctx.accounts.pool_token_account.amount, amount, CustomError::InsufficientFunds);
-    ctx.accounts.withdraw_token(amount)?;
+    require_gte!(ctx.accounts.pool_token_account.amount, amount, CustomError::InsufficientPoolTokens);
+    ctx.accounts.withdraw_token(amount, ctx.remaining_accounts)?;
 
+    emit!(TokenWithdrawn {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        destination: ctx.accounts.destination_token_account.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
 
@@ -67,26 +100,46 @@ pub fn handler_drain_token<'info>(
     ctx: Context<'_, '_, '_, 'info, WithdrawToken<'info>>
 ) -> Result<()> {
     let amount = ctx.accounts.pool_token_account.amount;
-    ctx.accounts.withdraw_token(amount)?;
+    ctx.accounts.withdraw_token(amount, ctx.remaining_accounts)?;
+    emit!(PoolDrained {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        kind: WithdrawalKind::Token,
+        destination: ctx.accounts.destination_token_account.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
     Ok(())
 }
 
 impl<'info> WithdrawToken<'info> {
-    fn withdraw_token(&self, amount: u64) -> Result<()> {
-        let authority = self.authority.key();
+    fn withdraw_token(&mut self, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        check_multisig_approval(
+            self.pool_config_account.multisig,
+            self.pool_multisig.as_ref(),
+            remaining_accounts
+        )?;
+        self.pool_config_account.record_outflow(
+            WithdrawalKind::Token,
+            amount,
+            Clock::get()?.unix_timestamp
+        )?;
+        let creator = self.pool_config_account.creator;
         let mint = self.token_mint_address.key();
+        let pool_index = self.pool_config_account.pool_index;
         let pool_config_account_bump = self.pool_config_account.pool_config_account_bump;
         let seeds = &[
             &[
                 POOL_CONFIG_ACCOUNT_SEED,
-                authority.as_ref(),
+                creator.as_ref(),
                 mint.as_ref(),
+                pool_index_seed(&pool_index),
                 bytemuck::bytes_of(&pool_config_account_bump),
             ][..],
         ];
         transfer_token_to_account(
             self.pool_token_account.to_account_info(),
-            self.master_authority_token_account.to_account_info(),
+            self.destination_token_account.to_account_info(),
             self.pool_config_account.to_account_info(),
             amount,
             self.token_program.to_account_info(),
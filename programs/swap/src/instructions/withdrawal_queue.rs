@@ -0,0 +1,403 @@
+use crate::constants::{
+    POOL_TOKEN_ACCOUNT_SEED,
+    POOL_CONFIG_ACCOUNT_SEED,
+    POOL_NATIVE_ACCOUNT_SEED,
+    POOL_MULTISIG_SEED,
+    POOL_ROLES_SEED,
+    PENDING_WITHDRAWAL_SEED,
+    MAX_WITHDRAWAL_DELAY,
+    PAUSE_WITHDRAW,
+    PROGRAM_CONFIG_SEED,
+    ROLE_TREASURER,
+};
+use crate::state::*;
+use crate::error::*;
+use crate::events::*;
+use crate::utils::*;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{ Mint, Token, TokenAccount };
+
+#[derive(Accounts)]
+pub struct SetWithdrawalDelay<'info> {
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = master_authority @ CustomError::WithdrawPermission,
+        has_one = authority @ CustomError::InvalidAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+    pub master_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueWithdrawal<'info> {
+    #[account(
+        init,
+        payer = master_authority,
+        seeds = [
+            PENDING_WITHDRAWAL_SEED,
+            pool_config_account.key().as_ref(),
+            pool_config_account.withdrawal_nonce.to_le_bytes().as_ref(),
+        ],
+        bump,
+        space = PendingWithdrawal::LEN
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = master_authority @ CustomError::WithdrawPermission,
+        constraint = !pool_config_account.is_paused(PAUSE_WITHDRAW) @ CustomError::WithdrawPaused,
+        constraint = pool_config_account.is_authorized(&authority.key(), pool_roles.as_deref(), ROLE_TREASURER) @ CustomError::InvalidAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ CustomError::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub master_authority: Signer<'info>,
+    /// CHECK: wallet for native, token account for token withdrawals, validated in the handler
+    pub destination: AccountInfo<'info>,
+    #[account(
+        seeds = [POOL_MULTISIG_SEED, pool_config_account.key().as_ref()],
+        bump = pool_multisig.bump
+    )]
+    pub pool_multisig: Option<Account<'info, PoolMultisig>>,
+    #[account(
+        seeds = [POOL_ROLES_SEED, pool_config_account.key().as_ref()],
+        bump = pool_roles.bump
+    )]
+    pub pool_roles: Option<Account<'info, PoolRoles>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    #[account(
+        mut,
+        close = payer,
+        has_one = pool_config_account,
+        has_one = destination @ CustomError::InvalidDestination,
+        has_one = payer
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+
+    #[account(
+        mut,
+        seeds = [
+            POOL_TOKEN_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+            pool_config_account.key().as_ref(),
+        ],
+        bump = pool_config_account.pool_token_account_bump,
+        token::mint = token_mint_address,
+        token::authority = pool_config_account
+    )]
+    pub pool_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: This account will be create when create swap pool
+    #[account(mut,
+        seeds=[
+            POOL_NATIVE_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+            pool_config_account.key().as_ref()
+        ],
+        bump = pool_config_account.pool_native_account_bump
+    )]
+    pub pool_native_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        constraint = !pool_config_account.is_paused(PAUSE_WITHDRAW) @ CustomError::WithdrawPaused
+    )]
+    pub pool_config_account: Box<Account<'info, PoolConfigAccount>>,
+    pub token_mint_address: Account<'info, Mint>,
+    #[account(
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ CustomError::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    /// CHECK: recorded in pending_withdrawal when it was queued
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+    /// CHECK: receives the pending_withdrawal rent back
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    #[account(
+        mut,
+        close = payer,
+        has_one = pool_config_account,
+        has_one = payer
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+
+    #[account(
+        seeds = [
+            POOL_CONFIG_ACCOUNT_SEED,
+            pool_config_account.creator.as_ref(),
+            token_mint_address.key().as_ref(),
+            pool_config_account.index_seed(),
+        ],
+        bump = pool_config_account.pool_config_account_bump,
+        has_one = authority @ CustomError::InvalidAuthority
+    )]
+    pub pool_config_account: Account<'info, PoolConfigAccount>,
+    pub token_mint_address: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+    /// CHECK: receives the pending_withdrawal rent back
+    #[account(mut)]
+    pub payer: AccountInfo<'info>,
+}
+
+// token withdrawals are allowlisted by the owner of the destination token account
+fn check_withdrawal_destination(
+    pool_config_account: &PoolConfigAccount,
+    kind: WithdrawalKind,
+    destination: &AccountInfo,
+    mint: &Pubkey
+) -> Result<()> {
+    let destination_owner = match kind {
+        WithdrawalKind::Token => {
+            let destination_token_account = Account::<TokenAccount>::try_from(destination)?;
+            require_keys_eq!(
+                destination_token_account.mint,
+                *mint,
+                CustomError::InvalidDestination
+            );
+            destination_token_account.owner
+        }
+        WithdrawalKind::Native => destination.key(),
+    };
+    require!(
+        pool_config_account.is_allowed_destination(&destination_owner),
+        CustomError::InvalidDestination
+    );
+    Ok(())
+}
+
+// the timelock is opt-in: pools start at 0 and withdraw directly until a delay is set here,
+// both keys sign so a single leaked key can't shorten the delay
+pub fn handler_set_withdrawal_delay<'info>(
+    ctx: Context<'_, '_, '_, 'info, SetWithdrawalDelay<'info>>,
+    withdrawal_delay: i64
+) -> Result<()> {
+    require!(
+        (0..=MAX_WITHDRAWAL_DELAY).contains(&withdrawal_delay),
+        CustomError::InvalidWithdrawalDelay
+    );
+    ctx.accounts.pool_config_account.withdrawal_delay = withdrawal_delay;
+
+    emit!(WithdrawalDelayUpdated {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        authority: ctx.accounts.authority.key(),
+        withdrawal_delay,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+pub fn handler_queue_withdrawal<'info>(
+    ctx: Context<'_, '_, '_, 'info, QueueWithdrawal<'info>>,
+    kind: WithdrawalKind,
+    amount: u64
+) -> Result<()> {
+    require_gt!(amount, 0, CustomError::InvalidAmount);
+    check_multisig_approval(
+        ctx.accounts.pool_config_account.multisig,
+        ctx.accounts.pool_multisig.as_ref(),
+        ctx.remaining_accounts
+    )?;
+    let destination = ctx.accounts.destination.key();
+    check_withdrawal_destination(
+        &ctx.accounts.pool_config_account,
+        kind,
+        &ctx.accounts.destination,
+        &ctx.accounts.token_mint_address.key()
+    )?;
+    let master_authority = ctx.accounts.master_authority.key();
+
+    let now = Clock::get()?.unix_timestamp;
+    let pool_config_account = &mut ctx.accounts.pool_config_account;
+    let pending_withdrawal = &mut ctx.accounts.pending_withdrawal;
+    pending_withdrawal.bump = *ctx.bumps.get("pending_withdrawal").unwrap();
+    pending_withdrawal.nonce = pool_config_account.withdrawal_nonce;
+    pending_withdrawal.pool_config_account = pool_config_account.key();
+    pending_withdrawal.kind = kind;
+    pending_withdrawal.amount = amount;
+    pending_withdrawal.destination = destination;
+    pending_withdrawal.unlock_at = now + pool_config_account.withdrawal_delay;
+    pending_withdrawal.payer = master_authority;
+
+    pool_config_account.withdrawal_nonce += 1;
+
+    emit!(WithdrawalQueued {
+        pool_config_account: pool_config_account.key(),
+        pending_withdrawal: pending_withdrawal.key(),
+        nonce: pending_withdrawal.nonce,
+        master_authority,
+        kind,
+        destination,
+        amount,
+        unlock_at: pending_withdrawal.unlock_at,
+        timestamp: now,
+    });
+    Ok(())
+}
+
+pub fn handler_execute_withdrawal<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteWithdrawal<'info>>
+) -> Result<()> {
+    let kind = ctx.accounts.pending_withdrawal.kind;
+    let amount = ctx.accounts.pending_withdrawal.amount;
+    // the allowlist may have changed while the withdrawal was locked
+    check_withdrawal_destination(
+        &ctx.accounts.pool_config_account,
+        kind,
+        &ctx.accounts.destination,
+        &ctx.accounts.token_mint_address.key()
+    )?;
+    let now = Clock::get()?.unix_timestamp;
+    require_gte!(now, ctx.accounts.pending_withdrawal.unlock_at, CustomError::WithdrawalLocked);
+    ctx.accounts.pool_config_account.record_outflow(kind, amount, now)?;
+    let pool_config_account = ctx.accounts.pool_config_account.key();
+    let authority = ctx.accounts.payer.key();
+    let destination = ctx.accounts.destination.key();
+    emit!(WithdrawalExecuted {
+        pool_config_account,
+        pending_withdrawal: ctx.accounts.pending_withdrawal.key(),
+        nonce: ctx.accounts.pending_withdrawal.nonce,
+        timestamp: now,
+    });
+    match kind {
+        WithdrawalKind::Token => {
+            ctx.accounts.withdraw_token(amount)?;
+            emit!(TokenWithdrawn {
+                pool_config_account,
+                authority,
+                destination,
+                amount,
+                timestamp: now,
+            });
+        }
+        WithdrawalKind::Native => {
+            ctx.accounts.withdraw_native(amount)?;
+            emit!(NativeWithdrawn {
+                pool_config_account,
+                authority,
+                destination,
+                lamports: amount,
+                timestamp: now,
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn handler_cancel_withdrawal<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelWithdrawal<'info>>
+) -> Result<()> {
+    emit!(WithdrawalCancelled {
+        pool_config_account: ctx.accounts.pool_config_account.key(),
+        pending_withdrawal: ctx.accounts.pending_withdrawal.key(),
+        nonce: ctx.accounts.pending_withdrawal.nonce,
+        authority: ctx.accounts.authority.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+impl<'info> ExecuteWithdrawal<'info> {
+    fn withdraw_token(&self, amount: u64) -> Result<()> {
+        require_gte!(self.pool_token_account.amount, amount, CustomError::InsufficientPoolTokens);
+        let creator = self.pool_config_account.creator;
+        let mint = self.token_mint_address.key();
+        let pool_index = self.pool_config_account.pool_index;
+        let pool_config_account_bump = self.pool_config_account.pool_config_account_bump;
+        let seeds = &[
+            &[
+                POOL_CONFIG_ACCOUNT_SEED,
+                creator.as_ref(),
+                mint.as_ref(),
+                pool_index_seed(&pool_index),
+                bytemuck::bytes_of(&pool_config_account_bump),
+            ][..],
+        ];
+        transfer_token_to_account(
+            self.pool_token_account.to_account_info(),
+            self.destination.to_account_info(),
+            self.pool_config_account.to_account_info(),
+            amount,
+            self.token_program.to_account_info(),
+            Some(seeds)
+        )?;
+
+        Ok(())
+    }
+
+    fn withdraw_native(&self, lamports: u64) -> Result<()> {
+        check_native_vault_withdraw(&self.pool_native_account, lamports)?;
+        let creator = self.pool_config_account.creator;
+        let mint = self.token_mint_address.key();
+        let pool_index = self.pool_config_account.pool_index;
+        let pool_config_account = self.pool_config_account.key();
+        let pool_native_account_bump = self.pool_config_account.pool_native_account_bump;
+        let seeds = &[
+            &[
+                POOL_NATIVE_ACCOUNT_SEED,
+                creator.as_ref(),
+                mint.as_ref(),
+                pool_index_seed(&pool_index),
+                pool_config_account.as_ref(),
+                bytemuck::bytes_of(&pool_native_account_bump),
+            ][..],
+        ];
+        transfer_native_to_account(
+            self.pool_native_account.to_account_info(),
+            self.destination.to_account_info(),
+            lamports,
+            self.system_program.to_account_info(),
+            Some(seeds)
+        )?;
+
+        Ok(())
+    }
+}
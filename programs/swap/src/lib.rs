@@ -3,8 +3,11 @@ pub mod constants;
 pub mod error;
 pub mod instructions;
 pub mod utils;
+pub mod events;
+pub mod quote;
 
 use crate::instructions::*;
+use crate::state::WithdrawalKind;
 use anchor_lang::prelude::*;
 
 declare_id!("EeZfadhHRPxs7QRrzzUA4t99auuxhXvScy8hELJpep8b");
@@ -15,20 +18,109 @@ pub mod swap {
 
     pub fn init_instruction<'info>(
         ctx: Context<'_, '_, '_, 'info, Initialize<'info>>,
-        token_price: u64
+        token_price: u64,
+        pool_index: u64,
+        price_denominator: u64
     ) -> Result<()> {
-        handler_init(ctx, token_price)?;
+        handler_init(ctx, token_price, pool_index, price_denominator)?;
         Ok(())
     }
 
-    pub fn update_config_instruction<'info>(
-        ctx: Context<'_, '_, '_, 'info, UpdateConfig<'info>>,
-        disable: bool
+    pub fn update_pool_params_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdatePoolParams<'info>>,
+        args: UpdatePoolParamsArgs
     ) -> Result<()> {
-        handler_update_config(ctx, disable)?;
+        handler_update_pool_params(ctx, args)?;
+        Ok(())
+    }
+
+    pub fn set_pause_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetPause<'info>>,
+        flags: u8,
+        paused: bool,
+        reason: u16
+    ) -> Result<()> {
+        handler_set_pause(ctx, flags, paused, reason)?;
+        Ok(())
+    }
+
+    pub fn set_guardian_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetGuardian<'info>>,
+        guardian: Pubkey
+    ) -> Result<()> {
+        handler_set_guardian(ctx, guardian)?;
+        Ok(())
+    }
+
+    pub fn guardian_pause_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, GuardianPause<'info>>,
+        flags: u8,
+        reason: u16
+    ) -> Result<()> {
+        handler_guardian_pause(ctx, flags, reason)?;
+        Ok(())
+    }
+
+    pub fn grant_role_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, GrantRole<'info>>,
+        member: Pubkey,
+        roles: u8
+    ) -> Result<()> {
+        handler_grant_role(ctx, member, roles)?;
+        Ok(())
+    }
+
+    pub fn revoke_role_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, RevokeRole<'info>>,
+        member: Pubkey,
+        roles: u8
+    ) -> Result<()> {
+        handler_revoke_role(ctx, member, roles)?;
+        Ok(())
+    }
+
+    pub fn migrate_pool_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigratePool<'info>>
+    ) -> Result<()> {
+        handler_migrate_pool(ctx)?;
+        Ok(())
+    }
 
+    pub fn init_program_config_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitProgramConfig<'info>>,
+        admin: Pubkey,
+        protocol_fee_recipient: Pubkey
+    ) -> Result<()> {
+        handler_init_program_config(ctx, admin, protocol_fee_recipient)?;
         Ok(())
     }
+
+    pub fn update_program_config_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateProgramConfig<'info>>,
+        admin: Option<Pubkey>,
+        protocol_fee_recipient: Option<Pubkey>,
+        creation_fee: Option<u64>
+    ) -> Result<()> {
+        handler_update_program_config(ctx, admin, protocol_fee_recipient, creation_fee)?;
+        Ok(())
+    }
+
+    pub fn set_global_pause_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateProgramConfig<'info>>,
+        paused: bool
+    ) -> Result<()> {
+        handler_set_global_pause(ctx, paused)?;
+        Ok(())
+    }
+
+    pub fn verify_pool_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, VerifyPool<'info>>,
+        verified: bool
+    ) -> Result<()> {
+        handler_verify_pool(ctx, verified)?;
+        Ok(())
+    }
+
     pub fn add_liquid_instruction<'info>(
         ctx: Context<'_, '_, '_, 'info, AddLiquid<'info>>,
         amount: u64
@@ -45,6 +137,16 @@ pub mod swap {
         Ok(())
     }
 
+    // read-only, the result comes back as return data, simulate it to preview a swap
+    pub fn quote_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, QuoteSwap<'info>>,
+        lamport_amount: u64
+    ) -> Result<SwapQuoteResult> {
+        handler_quote_swap(ctx, lamport_amount)
+    }
+
+    // direct withdraw and drain, open only while the pool's withdrawal_delay is 0 (the default),
+    // a pool opts into the timelock with set_withdrawal_delay and then withdraws through the queue
     pub fn withdraw_token_instruction<'info>(
         ctx: Context<'_, '_, '_, 'info, WithdrawToken<'info>>,
         amount: u64
@@ -74,4 +176,83 @@ pub mod swap {
         handler_drain_native(ctx)?;
         Ok(())
     }
+
+    pub fn propose_authority_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ProposeAuthority<'info>>,
+        new_authority: Pubkey
+    ) -> Result<()> {
+        handler_propose_authority(ctx, new_authority)?;
+        Ok(())
+    }
+
+    pub fn accept_authority_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptAuthority<'info>>
+    ) -> Result<()> {
+        handler_accept_authority(ctx)?;
+        Ok(())
+    }
+
+    pub fn propose_master_authority_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ProposeMasterAuthority<'info>>,
+        new_master_authority: Pubkey
+    ) -> Result<()> {
+        handler_propose_master_authority(ctx, new_master_authority)?;
+        Ok(())
+    }
+
+    pub fn accept_master_authority_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptMasterAuthority<'info>>
+    ) -> Result<()> {
+        handler_accept_master_authority(ctx)?;
+        Ok(())
+    }
+
+    pub fn set_multisig_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetMultisig<'info>>,
+        signers: Vec<Pubkey>,
+        threshold: u8
+    ) -> Result<()> {
+        handler_set_multisig(ctx, signers, threshold)?;
+        Ok(())
+    }
+
+    // new pools start at 0, any non-zero delay closes the direct withdraw and drain instructions
+    pub fn set_withdrawal_delay_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetWithdrawalDelay<'info>>,
+        withdrawal_delay: i64
+    ) -> Result<()> {
+        handler_set_withdrawal_delay(ctx, withdrawal_delay)?;
+        Ok(())
+    }
+
+    pub fn queue_withdrawal_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, QueueWithdrawal<'info>>,
+        kind: WithdrawalKind,
+        amount: u64
+    ) -> Result<()> {
+        handler_queue_withdrawal(ctx, kind, amount)?;
+        Ok(())
+    }
+
+    pub fn execute_withdrawal_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteWithdrawal<'info>>
+    ) -> Result<()> {
+        handler_execute_withdrawal(ctx)?;
+        Ok(())
+    }
+
+    pub fn cancel_withdrawal_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelWithdrawal<'info>>
+    ) -> Result<()> {
+        handler_cancel_withdrawal(ctx)?;
+        Ok(())
+    }
+
+    pub fn set_withdraw_destinations_instruction<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetWithdrawDestinations<'info>>,
+        destinations: Vec<Pubkey>
+    ) -> Result<()> {
+        handler_set_withdraw_destinations(ctx, destinations)?;
+        Ok(())
+    }
 }
\ No newline at end of file
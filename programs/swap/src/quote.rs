@@ -0,0 +1,191 @@
+// pure swap pricing shared by the program and off-chain clients, keep it free of std and account types
+use crate::constants::FEE_BPS_DENOMINATOR;
+use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub lamports_in: u64,
+    pub fee_lamports: u64,
+    pub tokens_out: u64,
+    // the price is fixed, so impact is the share of the token reserve the swap takes, capped at 100%
+    pub price_impact_bps: u16,
+}
+
+impl SwapQuote {
+    // token base units per SOL actually paid, fee included, 0 for prices under one base unit per SOL
+    pub fn effective_price(&self) -> u64 {
+        if self.lamports_in == 0 {
+            return 0;
+        }
+        (((self.tokens_out as u128) * (LAMPORTS_PER_SOL as u128)) / (self.lamports_in as u128)) as u64
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuoteError {
+    MathOverflow,
+    InvalidPrice,
+    AmountTooSmall,
+}
+
+// token_price base units cost price_denominator SOL, fees and tokens round down in the user's favour
+// and an input too small to buy one base unit is rejected rather than swapped for nothing
+pub fn quote_swap(
+    token_price: u64,
+    price_denominator: u64,
+    fee_bps: u16,
+    token_reserve: u64,
+    lamports_in: u64
+) -> Result<SwapQuote, QuoteError> {
+    if price_denominator == 0 {
+        return Err(QuoteError::InvalidPrice);
+    }
+    let fee_lamports =
+        ((lamports_in as u128) * (fee_bps as u128)) / (FEE_BPS_DENOMINATOR as u128);
+    let lamports_after_fee = (lamports_in as u128)
+        .checked_sub(fee_lamports)
+        .ok_or(QuoteError::MathOverflow)?;
+    let tokens_out = u64::try_from(
+        ((token_price as u128) * lamports_after_fee) /
+            ((price_denominator as u128) * (LAMPORTS_PER_SOL as u128))
+    ).map_err(|_| QuoteError::MathOverflow)?;
+    if tokens_out == 0 {
+        return Err(QuoteError::AmountTooSmall);
+    }
+    let price_impact_bps = if tokens_out >= token_reserve {
+        FEE_BPS_DENOMINATOR as u16
+    } else {
+        (((tokens_out as u128) * (FEE_BPS_DENOMINATOR as u128)) / (token_reserve as u128)) as u16
+    };
+
+    Ok(SwapQuote {
+        lamports_in,
+        fee_lamports: fee_lamports as u64,
+        tokens_out,
+        price_impact_bps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 10 tokens per SOL with 9 decimals
+    const PRICE: u64 = 10 * 1_000_000_000;
+    const RESERVE: u64 = 100 * 1_000_000_000;
+
+    #[test]
+    fn quotes_without_fee() {
+        let quote = quote_swap(PRICE, 1, 0, RESERVE, LAMPORTS_PER_SOL / 2).unwrap();
+        assert_eq!(quote, SwapQuote {
+            lamports_in: LAMPORTS_PER_SOL / 2,
+            fee_lamports: 0,
+            tokens_out: PRICE / 2,
+            price_impact_bps: 500,
+        });
+    }
+
+    #[test]
+    fn fee_is_taken_before_pricing() {
+        let quote = quote_swap(PRICE, 1, 100, RESERVE, LAMPORTS_PER_SOL).unwrap();
+        assert_eq!(quote.fee_lamports, LAMPORTS_PER_SOL / 100);
+        assert_eq!(quote.tokens_out, (PRICE * 99) / 100);
+    }
+
+    #[test]
+    fn effective_price_includes_the_fee() {
+        let quote = quote_swap(PRICE, 1, 0, RESERVE, LAMPORTS_PER_SOL / 2).unwrap();
+        assert_eq!(quote.effective_price(), PRICE);
+        let quote = quote_swap(PRICE, 1, 250, RESERVE, LAMPORTS_PER_SOL).unwrap();
+        assert_eq!(quote.effective_price(), (PRICE * 975) / 1_000);
+        let empty = SwapQuote { lamports_in: 0, fee_lamports: 0, tokens_out: 0, price_impact_bps: 0 };
+        assert_eq!(empty.effective_price(), 0);
+    }
+
+    #[test]
+    fn zero_input_is_too_small() {
+        assert_eq!(quote_swap(PRICE, 1, 100, RESERVE, 0), Err(QuoteError::AmountTooSmall));
+    }
+
+    #[test]
+    fn dust_is_too_small() {
+        // 1 lamport buys a hundredth of a base unit at 10 base units per SOL
+        assert_eq!(quote_swap(10, 1, 0, RESERVE, 1), Err(QuoteError::AmountTooSmall));
+        assert_eq!(quote_swap(10, 1, 0, RESERVE, LAMPORTS_PER_SOL / 10).unwrap().tokens_out, 1);
+        assert_eq!(
+            quote_swap(10, 1, 0, RESERVE, LAMPORTS_PER_SOL / 10 - 1),
+            Err(QuoteError::AmountTooSmall)
+        );
+        // a fee below one lamport is not charged
+        let quote = quote_swap(PRICE, 1, 100, RESERVE, 99).unwrap();
+        assert_eq!(quote.fee_lamports, 0);
+        assert_eq!(quote.tokens_out, 990);
+    }
+
+    #[test]
+    fn impact_is_capped_at_the_reserve() {
+        let quote = quote_swap(PRICE, 1, 0, RESERVE, 20 * LAMPORTS_PER_SOL).unwrap();
+        assert_eq!(quote.price_impact_bps, FEE_BPS_DENOMINATOR as u16);
+        let quote = quote_swap(PRICE, 1, 0, 0, 1).unwrap();
+        assert_eq!(quote.tokens_out, 10);
+        assert_eq!(quote.price_impact_bps, FEE_BPS_DENOMINATOR as u16);
+    }
+
+    #[test]
+    fn maximum_values() {
+        // the whole u64 range at one base unit per SOL still fits
+        let quote = quote_swap(LAMPORTS_PER_SOL, 1, 0, u64::MAX, u64::MAX).unwrap();
+        assert_eq!(quote.tokens_out, u64::MAX);
+        assert_eq!(quote.price_impact_bps, FEE_BPS_DENOMINATOR as u16);
+        assert_eq!(
+            quote_swap(u64::MAX, 1, 0, u64::MAX, u64::MAX),
+            Err(QuoteError::MathOverflow)
+        );
+        // a 100% fee leaves nothing to swap
+        assert_eq!(
+            quote_swap(PRICE, 1, FEE_BPS_DENOMINATOR as u16, RESERVE, u64::MAX),
+            Err(QuoteError::AmountTooSmall)
+        );
+    }
+
+    #[test]
+    fn fee_above_denominator_is_rejected() {
+        assert_eq!(
+            quote_swap(PRICE, 1, (FEE_BPS_DENOMINATOR as u16) + 1, RESERVE, LAMPORTS_PER_SOL),
+            Err(QuoteError::MathOverflow)
+        );
+    }
+
+    #[test]
+    fn prices_below_one_base_unit_per_sol() {
+        // 0.0037 SOL per whole token with 6 decimals
+        let lamports = (37 * LAMPORTS_PER_SOL) / 10_000;
+        let quote = quote_swap(10_000_000_000, 37, 0, RESERVE, lamports).unwrap();
+        assert_eq!(quote.tokens_out, 1_000_000);
+        // one base unit per 2 SOL
+        assert_eq!(quote_swap(1, 2, 0, RESERVE, 2 * LAMPORTS_PER_SOL).unwrap().tokens_out, 1);
+        assert_eq!(
+            quote_swap(1, 2, 0, RESERVE, 2 * LAMPORTS_PER_SOL - 1),
+            Err(QuoteError::AmountTooSmall)
+        );
+        // the denominator scales the same price
+        assert_eq!(
+            quote_swap(PRICE, 1, 100, RESERVE, LAMPORTS_PER_SOL),
+            quote_swap(3 * PRICE, 3, 100, RESERVE, LAMPORTS_PER_SOL)
+        );
+    }
+
+    #[test]
+    fn zero_denominator_is_rejected() {
+        assert_eq!(
+            quote_swap(PRICE, 0, 0, RESERVE, LAMPORTS_PER_SOL),
+            Err(QuoteError::InvalidPrice)
+        );
+    }
+
+    #[test]
+    fn maximum_denominator() {
+        let quote = quote_swap(u64::MAX, u64::MAX, 0, RESERVE, u64::MAX).unwrap();
+        assert_eq!(quote.tokens_out, u64::MAX / LAMPORTS_PER_SOL);
+    }
+}
@@ -1,24 +1,441 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::{
+    FEE_BPS_DENOMINATOR,
+    MAX_MULTISIG_SIGNERS,
+    MAX_ROLE_MEMBERS,
+    MAX_WITHDRAW_DESTINATIONS,
+    PAUSE_SWAP,
+    POOL_CONFIG_RESERVED_BYTES,
+};
+use crate::error::CustomError;
+use crate::quote::{ quote_swap, SwapQuote };
+use crate::utils::pool_index_seed;
+
 #[account]
 pub struct PoolConfigAccount {
     pub pool_config_account_bump: u8,
     pub pool_token_account_bump: u8,
     pub pool_native_account_bump: u8,
     pub token_price: u64,
-    pub is_active: bool,
+    pub pause_flags: u8, // PAUSE_* bits
     pub token_mint_address: Pubkey,
     pub pool_token_account: Pubkey,
     pub pool_native_account: Pubkey,
     pub master_authority: Pubkey, // wallet as a master permission to execute withdraw liquid
     pub authority: Pubkey,
+    pub creator: Pubkey, // authority at init, pool PDAs are derived from it so authority can rotate
+    pub pending_authority: Pubkey, // default pubkey when no transfer is proposed
+    pub pending_master_authority: Pubkey,
+    pub multisig: Pubkey, // default pubkey when withdrawals only need master_authority
+    pub withdrawal_delay: i64, // seconds, 0 allows immediate withdraw and drain
+    pub withdrawal_nonce: u64,
+    pub withdraw_destinations: [Pubkey; MAX_WITHDRAW_DESTINATIONS], // all default means any destination
+    pub outflow_window: i64, // seconds, 0 disables outflow limits
+    pub outflow_window_start: i64,
+    pub token_outflow_limit: u64, // 0 means unlimited
+    pub native_outflow_limit: u64,
+    pub token_outflow_in_window: u64,
+    pub native_outflow_in_window: u64,
+    pub fee_bps: u16, // swap fee on the lamports paid in, kept by the native vault
+    pub max_price_change_bps: u16, // larger moves need master_authority, 0 disables the check
+    pub price_update_cooldown: i64, // seconds between price updates
+    pub last_price_update_at: i64,
+    pub pause_reason: u16, // reason code recorded by the last pause change
+    pub guardian: Pubkey, // can only add pause flags, default = no guardian
+    // cumulative statistics, saturating so they never block a swap or deposit
+    pub swap_count: u64,
+    pub total_lamports_in: u64,
+    pub total_tokens_out: u64,
+    pub unique_buyers: u64, // counted when a user's stats account is created
+    pub total_liquidity_added: u64,
+    pub last_swap_at: i64,
+    pub version: u8, // POOL_CONFIG_VERSION once initialized or migrated
+    pub pool_index: u64, // lets one creator run several pools of the same mint
+    pub is_verified: bool, // set by the program admin, new pools start unverified
+    pub price_denominator: u64, // token_price base units cost price_denominator SOL
+    // outflows of the window before outflow_window_start, weighted into the sliding limit
+    pub token_outflow_prev_window: u64,
+    pub native_outflow_prev_window: u64,
+    // POOL_CONFIG_RESERVED_BYTES split in two, borsh 0.9 has no impl for arrays of most lengths over 32
+    pub reserved: [u8; 64],
+    pub reserved_tail: [u8; 31], // new fields take their space from here first
 }
 
 impl PoolConfigAccount {
     pub const LEN: usize =
         8 + //
+        2 * 3 + // u16
+        5 + // u8
+        8 * 15 + // u64
+        8 * 6 + // i64
         1 + // boolean
-        1 * 3 + // u8
-        8 * 1 + // u64
-        5 * 32; // Pubkey
-}
\ No newline at end of file
+        10 * 32 + // Pubkey
+        32 * MAX_WITHDRAW_DESTINATIONS + // [Pubkey; MAX_WITHDRAW_DESTINATIONS]
+        POOL_CONFIG_RESERVED_BYTES; // reserved + reserved_tail
+
+    // the authority keeps every role, other keys need it granted in pool_roles
+    pub fn is_authorized(&self, key: &Pubkey, pool_roles: Option<&PoolRoles>, role: u8) -> bool {
+        *key == self.authority || matches!(pool_roles, Some(roles) if roles.has_role(key, role))
+    }
+
+    pub fn index_seed(&self) -> &[u8] {
+        pool_index_seed(&self.pool_index)
+    }
+
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.pause_flags & flag != 0
+    }
+
+    // pools from before version 2 have no denominator stored and were priced per whole SOL
+    pub fn price_denominator(&self) -> u64 {
+        self.price_denominator.max(1)
+    }
+
+    pub fn params(&self) -> PoolParams {
+        PoolParams {
+            token_price: self.token_price,
+            price_denominator: self.price_denominator(),
+            fee_bps: self.fee_bps,
+            outflow_window: self.outflow_window,
+            token_outflow_limit: self.token_outflow_limit,
+            native_outflow_limit: self.native_outflow_limit,
+            max_price_change_bps: self.max_price_change_bps,
+            price_update_cooldown: self.price_update_cooldown,
+            pause_flags: self.pause_flags,
+            is_verified: self.is_verified,
+        }
+    }
+
+    // relative move from the current price in basis points, both ratios cross-multiplied
+    pub fn price_change_bps(&self, token_price: u64, price_denominator: u64) -> u128 {
+        let current = (self.token_price as u128) * (price_denominator as u128);
+        let new = (token_price as u128) * (self.price_denominator() as u128);
+        current.abs_diff(new).saturating_mul(FEE_BPS_DENOMINATOR as u128) / current.max(1)
+    }
+
+    pub fn is_allowed_destination(&self, owner: &Pubkey) -> bool {
+        let unrestricted = self.withdraw_destinations
+            .iter()
+            .all(|destination| *destination == Pubkey::default());
+        unrestricted || self.withdraw_destinations.contains(owner)
+    }
+
+    // count an outflow against a sliding window: the current bucket plus the previous one
+    // weighted by how much of it still overlaps the last `outflow_window` seconds
+    pub fn record_outflow(&mut self, kind: WithdrawalKind, amount: u64, now: i64) -> Result<()> {
+        if self.outflow_window == 0 {
+            return Ok(());
+        }
+        let elapsed_windows = now.saturating_sub(self.outflow_window_start) / self.outflow_window;
+        if elapsed_windows == 1 {
+            // buckets stay aligned so the previous one always ends where the current one starts
+            self.outflow_window_start += self.outflow_window;
+            self.token_outflow_prev_window = self.token_outflow_in_window;
+            self.native_outflow_prev_window = self.native_outflow_in_window;
+            self.token_outflow_in_window = 0;
+            self.native_outflow_in_window = 0;
+        } else if elapsed_windows > 1 {
+            self.outflow_window_start = now;
+            self.token_outflow_prev_window = 0;
+            self.native_outflow_prev_window = 0;
+            self.token_outflow_in_window = 0;
+            self.native_outflow_in_window = 0;
+        }
+        let remaining = (self.outflow_window - now.saturating_sub(self.outflow_window_start)).max(0);
+        let (limit, previous, outflow) = match kind {
+            WithdrawalKind::Token =>
+                (self.token_outflow_limit, self.token_outflow_prev_window, &mut self.token_outflow_in_window),
+            WithdrawalKind::Native =>
+                (self.native_outflow_limit, self.native_outflow_prev_window, &mut self.native_outflow_in_window),
+        };
+        let total = outflow.checked_add(amount).ok_or(CustomError::OutflowLimitExceeded)?;
+        let weighted_previous =
+            ((previous as u128) * (remaining as u128)) / (self.outflow_window as u128);
+        require!(
+            limit == 0 || weighted_previous + (total as u128) <= (limit as u128),
+            CustomError::OutflowLimitExceeded
+        );
+        *outflow = total;
+        Ok(())
+    }
+
+    // a new window length invalidates both buckets, new limits apply to what already left
+    pub fn restart_outflow_window(&mut self, now: i64) {
+        self.outflow_window_start = now;
+        self.token_outflow_prev_window = 0;
+        self.native_outflow_prev_window = 0;
+        self.token_outflow_in_window = 0;
+        self.native_outflow_in_window = 0;
+    }
+
+    // the checks a swap has to pass, quote_swap runs them too so a quote never promises a failing swap
+    pub fn swap_quote(&self, token_reserve: u64, lamport_amount: u64) -> Result<SwapQuote> {
+        require!(!self.is_paused(PAUSE_SWAP), CustomError::DeactivatePool);
+        let quote = quote_swap(
+            self.token_price,
+            self.price_denominator(),
+            self.fee_bps,
+            token_reserve,
+            lamport_amount
+        ).map_err(CustomError::from)?;
+        require_gte!(token_reserve, quote.tokens_out, CustomError::InsufficientPoolTokens);
+        Ok(quote)
+    }
+
+    pub fn record_swap(&mut self, lamports_in: u64, tokens_out: u64, new_buyer: bool, now: i64) {
+        self.swap_count = self.swap_count.saturating_add(1);
+        self.total_lamports_in = self.total_lamports_in.saturating_add(lamports_in);
+        self.total_tokens_out = self.total_tokens_out.saturating_add(tokens_out);
+        if new_buyer {
+            self.unique_buyers = self.unique_buyers.saturating_add(1);
+        }
+        self.last_swap_at = now;
+    }
+}
+
+// settings changed by update_pool_params, recorded before and after each update
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PoolParams {
+    pub token_price: u64,
+    pub price_denominator: u64,
+    pub fee_bps: u16,
+    pub outflow_window: i64,
+    pub token_outflow_limit: u64,
+    pub native_outflow_limit: u64,
+    pub max_price_change_bps: u16,
+    pub price_update_cooldown: i64,
+    pub pause_flags: u8,
+    pub is_verified: bool,
+}
+
+#[account]
+pub struct PoolMultisig {
+    pub bump: u8,
+    pub pool_config_account: Pubkey,
+    pub threshold: u8,
+    pub signers: Vec<Pubkey>,
+}
+
+impl PoolMultisig {
+    pub const LEN: usize =
+        8 + //
+        2 + // u8
+        32 + // Pubkey
+        4 + 32 * MAX_MULTISIG_SIGNERS; // Vec<Pubkey>
+
+    // distinct multisig members that signed the transaction
+    pub fn approvals(&self, accounts: &[AccountInfo]) -> usize {
+        let mut approved: Vec<&Pubkey> = Vec::new();
+        for account in accounts.iter().filter(|account| account.is_signer) {
+            if self.signers.contains(account.key) && !approved.contains(&account.key) {
+                approved.push(account.key);
+            }
+        }
+        approved.len()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RoleMember {
+    pub member: Pubkey,
+    pub roles: u8, // ROLE_* bits
+}
+
+#[account]
+pub struct PoolRoles {
+    pub bump: u8,
+    pub pool_config_account: Pubkey,
+    pub members: Vec<RoleMember>,
+}
+
+impl PoolRoles {
+    pub const LEN: usize =
+        8 + //
+        1 + // u8
+        32 + // Pubkey
+        4 + (32 + 1) * MAX_ROLE_MEMBERS; // Vec<RoleMember>
+
+    pub fn has_role(&self, key: &Pubkey, role: u8) -> bool {
+        self.members.iter().any(|member| member.member == *key && member.roles & role != 0)
+    }
+}
+
+// singleton holding protocol-wide settings
+#[account]
+pub struct ProgramConfig {
+    pub bump: u8,
+    pub admin: Pubkey,
+    pub protocol_fee_recipient: Pubkey,
+    pub paused: bool, // global kill switch for pool creation and fund movements
+    pub pool_count: u64, // next registry index
+    pub creation_fee: u64, // lamports paid to protocol_fee_recipient by every pool creator
+}
+
+impl ProgramConfig {
+    pub const LEN: usize =
+        8 + //
+        1 + // u8
+        32 * 2 + // Pubkey
+        1 + // boolean
+        8 * 2; // u64
+}
+
+// one per created pool, pools are listed by reading indexes 0..pool_count
+#[account]
+pub struct PoolRegistryEntry {
+    pub bump: u8,
+    pub index: u64,
+    pub pool_config_account: Pubkey,
+    pub token_mint_address: Pubkey,
+    pub creator: Pubkey,
+    pub created_at: i64,
+}
+
+impl PoolRegistryEntry {
+    pub const LEN: usize =
+        8 + //
+        1 + // u8
+        8 + // u64
+        32 * 3 + // Pubkey
+        8; // i64
+}
+
+#[account]
+pub struct UserStats {
+    pub bump: u8,
+    pub pool_config_account: Pubkey,
+    pub user: Pubkey,
+    pub swap_count: u64,
+    pub lamports_in: u64,
+    pub tokens_out: u64,
+    pub last_swap_at: i64,
+}
+
+impl UserStats {
+    pub const LEN: usize =
+        8 + //
+        1 + // u8
+        32 * 2 + // Pubkey
+        8 * 3 + // u64
+        8; // i64
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum WithdrawalKind {
+    Token,
+    Native,
+}
+
+#[account]
+pub struct PendingWithdrawal {
+    pub bump: u8,
+    pub nonce: u64,
+    pub pool_config_account: Pubkey,
+    pub kind: WithdrawalKind,
+    pub amount: u64,
+    pub destination: Pubkey, // wallet for native, token account for token
+    pub unlock_at: i64,
+    pub payer: Pubkey, // refunded when the withdrawal is executed or cancelled
+}
+
+impl PendingWithdrawal {
+    pub const LEN: usize =
+        8 + //
+        1 + // u8
+        1 + // enum
+        8 * 2 + // u64
+        8 + // i64
+        3 * 32; // Pubkey
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::LEGACY_POOL_CONFIG_LEN;
+
+    #[test]
+    fn pool_config_account_len_matches_serialized_size() {
+        let config_account = PoolConfigAccount::try_from_slice(
+            &vec![0u8; PoolConfigAccount::LEN - 8]
+        ).unwrap();
+        assert_eq!(config_account.try_to_vec().unwrap().len() + 8, PoolConfigAccount::LEN);
+    }
+
+    // migrate_pool relies on the unversioned layout being a prefix of the current one
+    #[test]
+    fn legacy_layout_is_a_prefix_of_pool_config_account() {
+        let authority = Pubkey::new_unique();
+        let mut data = vec![1u8, 2, 3];
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.push(1); // is_active
+        for _ in 0..4 {
+            data.extend_from_slice(Pubkey::new_unique().as_ref());
+        }
+        data.extend_from_slice(authority.as_ref());
+        assert_eq!(data.len() + 8, LEGACY_POOL_CONFIG_LEN);
+
+        data.resize(PoolConfigAccount::LEN - 8, 0);
+        let config_account = PoolConfigAccount::try_from_slice(&data).unwrap();
+        assert_eq!(config_account.token_price, 42);
+        assert_eq!(config_account.pause_flags, 1);
+        assert_eq!(config_account.authority, authority);
+        assert_eq!(config_account.version, 0);
+        assert_eq!(config_account.price_denominator(), 1);
+    }
+
+    #[test]
+    fn outflow_window_ending_past_i64_max_never_expires() {
+        let mut config_account = PoolConfigAccount::try_from_slice(
+            &vec![0u8; PoolConfigAccount::LEN - 8]
+        ).unwrap();
+        config_account.outflow_window = i64::MAX;
+        config_account.outflow_window_start = 1;
+        config_account.token_outflow_limit = 10;
+        config_account.record_outflow(WithdrawalKind::Token, 6, i64::MAX - 1).unwrap();
+        assert_eq!(config_account.outflow_window_start, 1);
+        assert!(config_account.record_outflow(WithdrawalKind::Token, 6, i64::MAX - 1).is_err());
+    }
+
+    #[test]
+    fn outflow_limit_slides_across_window_boundaries() {
+        let mut config_account = PoolConfigAccount::try_from_slice(
+            &vec![0u8; PoolConfigAccount::LEN - 8]
+        ).unwrap();
+        config_account.outflow_window = 100;
+        config_account.native_outflow_limit = 100;
+        config_account.record_outflow(WithdrawalKind::Native, 100, 90).unwrap();
+
+        // 90% of the previous window still overlaps, a full limit can't leave right after the boundary
+        config_account.record_outflow(WithdrawalKind::Native, 10, 110).unwrap();
+        assert!(config_account.record_outflow(WithdrawalKind::Native, 1, 110).is_err());
+        assert_eq!(config_account.outflow_window_start, 100);
+        assert_eq!(config_account.native_outflow_prev_window, 100);
+
+        config_account.record_outflow(WithdrawalKind::Native, 80, 190).unwrap();
+        assert!(config_account.record_outflow(WithdrawalKind::Native, 1, 190).is_err());
+
+        // both buckets expired
+        config_account.record_outflow(WithdrawalKind::Native, 100, 400).unwrap();
+        assert_eq!(config_account.outflow_window_start, 400);
+        assert_eq!(config_account.native_outflow_prev_window, 0);
+        // token outflows are tracked separately
+        config_account.record_outflow(WithdrawalKind::Token, 1_000, 400).unwrap();
+    }
+
+    #[test]
+    fn fixed_size_accounts_len_matches_serialized_size() {
+        let user_stats = UserStats::try_from_slice(&[0u8; UserStats::LEN - 8]).unwrap();
+        assert_eq!(user_stats.try_to_vec().unwrap().len() + 8, UserStats::LEN);
+        let pending_withdrawal = PendingWithdrawal::try_from_slice(
+            &[0u8; PendingWithdrawal::LEN - 8]
+        ).unwrap();
+        assert_eq!(pending_withdrawal.try_to_vec().unwrap().len() + 8, PendingWithdrawal::LEN);
+        let program_config = ProgramConfig::try_from_slice(&[0u8; ProgramConfig::LEN - 8]).unwrap();
+        assert_eq!(program_config.try_to_vec().unwrap().len() + 8, ProgramConfig::LEN);
+        let pool_registry_entry = PoolRegistryEntry::try_from_slice(
+            &[0u8; PoolRegistryEntry::LEN - 8]
+        ).unwrap();
+        assert_eq!(pool_registry_entry.try_to_vec().unwrap().len() + 8, PoolRegistryEntry::LEN);
+    }
+}
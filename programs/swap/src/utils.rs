@@ -1,18 +1,67 @@
+use crate::constants::MAX_TOKENS_PER_SOL;
 use crate::error::CustomError;
+use crate::state::PoolMultisig;
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
 use anchor_spl::token::{ InitializeAccount, Transfer };
 
-pub fn transfer_native_pda_to_account<'info>(
-    escrow_vault: AccountInfo<'info>,
-    receive_account: AccountInfo<'info>,
-    amount: u64
+// pool index 0 adds no seed bytes so pools created before indexes keep their addresses
+pub fn pool_index_seed(pool_index: &u64) -> &[u8] {
+    if *pool_index == 0 { &[] } else { bytemuck::bytes_of(pool_index) }
+}
+
+// a price has to buy at least one base unit for the largest swap the program can take
+pub fn check_token_price(token_price: u64, price_denominator: u64) -> Result<()> {
+    require_gt!(token_price, 0, CustomError::InvalidPrice);
+    require_gt!(price_denominator, 0, CustomError::InvalidPrice);
+    let max_tokens_out =
+        ((token_price as u128) * (u64::MAX as u128)) /
+        ((price_denominator as u128) * (LAMPORTS_PER_SOL as u128));
+    require_gt!(max_tokens_out, 0, CustomError::InvalidPrice);
+    Ok(())
+}
+
+// the bounds a pool price has to meet at init and on every later update
+pub fn check_pool_price(token_price: u64, price_denominator: u64, decimals: u8) -> Result<()> {
+    check_token_price(token_price, price_denominator)?;
+    let max_token_price = (MAX_TOKENS_PER_SOL as u128)
+        .checked_mul(10u128.pow(decimals as u32))
+        .and_then(|max| max.checked_mul(price_denominator as u128))
+        .ok_or(CustomError::InvalidPrice)?;
+    require_gte!(max_token_price, token_price as u128, CustomError::InvalidPrice);
+    Ok(())
+}
+
+// lamports a native vault can release while staying rent exempt
+pub fn native_vault_withdrawable(vault: &AccountInfo) -> Result<u64> {
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(vault.data_len());
+    Ok(vault.lamports().saturating_sub(rent_exempt_minimum))
+}
+
+// every lamport leaving a native vault goes through this check, so the vault stays rent exempt
+pub fn check_native_vault_withdraw(vault: &AccountInfo, amount: u64) -> Result<()> {
+    require_gte!(vault.lamports(), amount, CustomError::InsufficientPoolLamports);
+    require_gte!(native_vault_withdrawable(vault)?, amount, CustomError::NativeVaultRentExempt);
+    Ok(())
+}
+
+// pools with a multisig need `threshold` of its signers in remaining_accounts
+pub fn check_multisig_approval<'info>(
+    multisig_address: Pubkey,
+    pool_multisig: Option<&Account<'info, PoolMultisig>>,
+    remaining_accounts: &[AccountInfo<'info>]
 ) -> Result<()> {
-    if **escrow_vault.try_borrow_lamports()? < amount.into() {
-        return Err(CustomError::InsufficientFunds.into());
+    if multisig_address == Pubkey::default() {
+        return Ok(());
     }
-    **escrow_vault.try_borrow_mut_lamports()? -= amount;
-    **receive_account.try_borrow_mut_lamports()? += amount;
+    let pool_multisig = pool_multisig.ok_or(CustomError::InvalidMultisig)?;
+    require_keys_eq!(pool_multisig.key(), multisig_address, CustomError::InvalidMultisig);
+    require_gte!(
+        pool_multisig.approvals(remaining_accounts),
+        pool_multisig.threshold as usize,
+        CustomError::MultisigThresholdNotMet
+    );
     Ok(())
 }
 
@@ -144,25 +193,3 @@ pub fn close_token_account<'info>(
     anchor_spl::token::close_account(cpi_ctx)?;
     Ok(())
 }
-
-pub fn close_native_account<'info>(
-    from_pubkey: AccountInfo<'info>,
-    to_pubkey: AccountInfo<'info>,
-    signers_seeds: &[&[&[u8]]],
-    system_program: AccountInfo<'info>
-) -> Result<()> {
-    anchor_lang::solana_program::program::invoke_signed(
-        &anchor_lang::solana_program::system_instruction::transfer(
-            from_pubkey.key,
-            to_pubkey.key,
-            from_pubkey.lamports()
-        ),
-        &[
-            from_pubkey.to_account_info(),
-            to_pubkey.to_account_info(),
-            system_program.to_account_info(),
-        ],
-        signers_seeds
-    )?;
-    Ok(())
-}
\ No newline at end of file
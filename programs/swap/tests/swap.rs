@@ -0,0 +1,743 @@
+use anchor_lang::prelude::{ AccountInfo, Pubkey };
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::{ system_program, sysvar };
+use anchor_lang::{ AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas };
+use anchor_spl::associated_token::{ self, get_associated_token_address };
+use anchor_spl::token::spl_token;
+use solana_program_test::{ processor, BanksClientError, ProgramTest, ProgramTestContext };
+use solana_sdk::account::{ Account, AccountSharedData };
+use solana_sdk::instruction::{ Instruction, InstructionError };
+use solana_sdk::native_loader;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{ Keypair, Signer };
+use solana_sdk::transaction::{ Transaction, TransactionError };
+use swap::constants::{
+    LEGACY_POOL_CONFIG_LEN,
+    PAUSE_ALL,
+    POOL_CONFIG_ACCOUNT_SEED,
+    POOL_NATIVE_ACCOUNT_SEED,
+    POOL_REGISTRY_SEED,
+    POOL_TOKEN_ACCOUNT_SEED,
+    PROGRAM_CONFIG_SEED,
+    USER_STATS_SEED,
+};
+use swap::error::CustomError;
+use swap::state::{ PoolConfigAccount, ProgramConfig, UserStats };
+
+const DECIMALS: u8 = 9;
+// 10 tokens per SOL
+const TOKEN_PRICE: u64 = 10 * 1_000_000_000;
+const MINTED: u64 = 1_000 * 1_000_000_000;
+const LIQUIDITY: u64 = 100 * 1_000_000_000;
+
+// anchor's entry ties the account slice to 'info, program-test hands us a shorter borrow
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    swap::entry(program_id, accounts, data)
+}
+
+struct Keys {
+    authority: Keypair,
+    master_authority: Keypair,
+    user: Keypair,
+}
+
+struct Pool {
+    context: ProgramTestContext,
+    authority: Pubkey,
+    master_authority: Pubkey,
+    user: Pubkey,
+    mint: Pubkey,
+    authority_token_account: Pubkey,
+    master_token_account: Pubkey,
+    program_config: Pubkey,
+    pool_config_account: Pubkey,
+    pool_token_account: Pubkey,
+    pool_native_account: Pubkey,
+}
+
+fn wallet(lamports: u64) -> Account {
+    Account::new(lamports, 0, &system_program::ID)
+}
+
+fn packed<T: Pack>(state: T, rent: u64) -> Account {
+    let mut data = vec![0; T::LEN];
+    T::pack(state, &mut data).unwrap();
+    Account { lamports: rent, data, owner: spl_token::ID, executable: false, rent_epoch: 0 }
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Account {
+    packed(
+        spl_token::state::Account {
+            mint,
+            owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        },
+        LAMPORTS_PER_SOL
+    )
+}
+
+impl Pool {
+    // a fresh validator with the program config in place and an uninitialized pool
+    async fn start() -> (Self, Keys) {
+        Self::start_with_decimals(DECIMALS).await
+    }
+
+    async fn start_with_decimals(decimals: u8) -> (Self, Keys) {
+        let mut program_test = ProgramTest::new("swap", swap::ID, processor!(process_instruction));
+        program_test.prefer_bpf(false);
+        // run spl token and the ata program natively too, 1.14's bpf vm can't start on current
+        // toolchains and its bundled .so accounts win over builtins unless overridden
+        program_test.add_program(
+            "spl_token",
+            spl_token::ID,
+            processor!(spl_token::processor::Processor::process)
+        );
+        program_test.add_program(
+            "spl_associated_token_account",
+            associated_token::ID,
+            processor!(spl_associated_token_account::processor::process_instruction)
+        );
+        for (name, program_id) in [
+            ("spl_token", spl_token::ID),
+            ("spl_associated_token_account", associated_token::ID),
+        ] {
+            program_test.add_account(program_id, native_loader::create_loadable_account_for_test(name).into());
+        }
+
+        let authority = Keypair::new();
+        let master_authority = Keypair::new();
+        let user = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let authority_token_account = Pubkey::new_unique();
+        let master_token_account = Pubkey::new_unique();
+        program_test.add_account(authority.pubkey(), wallet(10 * LAMPORTS_PER_SOL));
+        program_test.add_account(master_authority.pubkey(), wallet(10 * LAMPORTS_PER_SOL));
+        program_test.add_account(user.pubkey(), wallet(10 * LAMPORTS_PER_SOL));
+        program_test.add_account(
+            mint,
+            packed(
+                spl_token::state::Mint {
+                    mint_authority: COption::Some(authority.pubkey()),
+                    supply: MINTED,
+                    decimals,
+                    is_initialized: true,
+                    freeze_authority: COption::None,
+                },
+                LAMPORTS_PER_SOL
+            )
+        );
+        program_test.add_account(
+            authority_token_account,
+            token_account(mint, authority.pubkey(), MINTED)
+        );
+        program_test.add_account(
+            master_token_account,
+            token_account(mint, master_authority.pubkey(), 0)
+        );
+
+        let (program_config, bump) = Pubkey::find_program_address(
+            &[PROGRAM_CONFIG_SEED],
+            &swap::ID
+        );
+        let mut data = Vec::with_capacity(ProgramConfig::LEN);
+        (ProgramConfig {
+            bump,
+            admin: authority.pubkey(),
+            protocol_fee_recipient: master_authority.pubkey(),
+            paused: false,
+            pool_count: 0,
+            creation_fee: 0,
+        })
+            .try_serialize(&mut data)
+            .unwrap();
+        data.resize(ProgramConfig::LEN, 0);
+        program_test.add_account(program_config, Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: swap::ID,
+            executable: false,
+            rent_epoch: 0,
+        });
+
+        let (pool_config_account, _) = Pubkey::find_program_address(
+            &[POOL_CONFIG_ACCOUNT_SEED, authority.pubkey().as_ref(), mint.as_ref()],
+            &swap::ID
+        );
+        let (pool_token_account, _) = Pubkey::find_program_address(
+            &[
+                POOL_TOKEN_ACCOUNT_SEED,
+                authority.pubkey().as_ref(),
+                mint.as_ref(),
+                pool_config_account.as_ref(),
+            ],
+            &swap::ID
+        );
+        let (pool_native_account, _) = Pubkey::find_program_address(
+            &[
+                POOL_NATIVE_ACCOUNT_SEED,
+                authority.pubkey().as_ref(),
+                mint.as_ref(),
+                pool_config_account.as_ref(),
+            ],
+            &swap::ID
+        );
+
+        let pool = Self {
+            context: program_test.start_with_context().await,
+            authority: authority.pubkey(),
+            master_authority: master_authority.pubkey(),
+            user: user.pubkey(),
+            mint,
+            authority_token_account,
+            master_token_account,
+            program_config,
+            pool_config_account,
+            pool_token_account,
+            pool_native_account,
+        };
+        (pool, Keys { authority, master_authority, user })
+    }
+
+    // the pool after init and a first deposit, ready for swaps and withdrawals
+    async fn funded() -> (Self, Keys) {
+        let (mut pool, keys) = Self::start().await;
+        pool.process(pool.init_instruction(), &[&keys.authority]).await.unwrap();
+        pool.process(pool.add_liquid_instruction(LIQUIDITY), &[&keys.authority]).await.unwrap();
+        (pool, keys)
+    }
+
+    async fn process(
+        &mut self,
+        instruction: Instruction,
+        signers: &[&Keypair]
+    ) -> Result<(), BanksClientError> {
+        // a fresh blockhash keeps a retried instruction from being deduplicated, warping records one,
+        // the working bank sits one slot past the root
+        let root_slot = self.context.banks_client.get_root_slot().await.unwrap();
+        self.context.warp_to_slot(root_slot + 2).unwrap();
+        let blockhash = self.context.banks_client.get_latest_blockhash().await.unwrap();
+        let mut keypairs = vec![&self.context.payer];
+        keypairs.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.context.payer.pubkey()),
+            &keypairs,
+            blockhash
+        );
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    async fn account<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn config(&mut self) -> PoolConfigAccount {
+        self.account(self.pool_config_account).await
+    }
+
+    async fn token_balance(&mut self, address: Pubkey) -> u64 {
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.context.banks_client.get_balance(address).await.unwrap()
+    }
+
+    fn user_token_account(&self) -> Pubkey {
+        get_associated_token_address(&self.user, &self.mint)
+    }
+
+    fn user_stats(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[USER_STATS_SEED, self.pool_config_account.as_ref(), self.user.as_ref()],
+            &swap::ID
+        ).0
+    }
+
+    fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        Instruction {
+            program_id: swap::ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        }
+    }
+
+    fn init_instruction(&self) -> Instruction {
+        self.init_instruction_with_price(TOKEN_PRICE, 1)
+    }
+
+    fn init_instruction_with_price(&self, token_price: u64, price_denominator: u64) -> Instruction {
+        Self::instruction(
+            swap::accounts::Initialize {
+                pool_token_account: self.pool_token_account,
+                pool_native_account: self.pool_native_account,
+                pool_config_account: self.pool_config_account,
+                token_mint_address: self.mint,
+                program_config: self.program_config,
+                pool_registry_entry: Pubkey::find_program_address(
+                    &[POOL_REGISTRY_SEED, 0u64.to_le_bytes().as_ref()],
+                    &swap::ID
+                ).0,
+                protocol_fee_recipient: self.master_authority,
+                authority: self.authority,
+                master_authority: self.master_authority,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                rent: sysvar::rent::ID,
+            },
+            swap::instruction::InitInstruction { token_price, pool_index: 0, price_denominator }
+        )
+    }
+
+    fn add_liquid_instruction(&self, amount: u64) -> Instruction {
+        Self::instruction(
+            swap::accounts::AddLiquid {
+                pool_token_account: self.pool_token_account,
+                pool_config_account: self.pool_config_account,
+                token_mint_address: self.mint,
+                program_config: self.program_config,
+                authority: self.authority,
+                depositor_token_account: self.authority_token_account,
+                depositor: self.authority,
+                pool_roles: None,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+            },
+            swap::instruction::AddLiquidInstruction { amount }
+        )
+    }
+
+    fn swap_instruction(&self, lamport_amount: u64) -> Instruction {
+        Self::instruction(
+            swap::accounts::SwapToken {
+                pool_token_account: self.pool_token_account,
+                pool_native_account: self.pool_native_account,
+                pool_config_account: self.pool_config_account,
+                user_token_account: self.user_token_account(),
+                user_stats: self.user_stats(),
+                token_mint_address: self.mint,
+                program_config: self.program_config,
+                authority: self.authority,
+                user: self.user,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                rent: sysvar::rent::ID,
+                associated_token_program: associated_token::ID,
+            },
+            swap::instruction::SwapToken { lamport_amount }
+        )
+    }
+
+    fn quote_instruction(&self, lamport_amount: u64) -> Instruction {
+        Self::instruction(
+            swap::accounts::QuoteSwap {
+                pool_token_account: self.pool_token_account,
+                pool_native_account: self.pool_native_account,
+                pool_config_account: self.pool_config_account,
+                user_token_account: self.user_token_account(),
+                user_stats: self.user_stats(),
+                token_mint_address: self.mint,
+                program_config: self.program_config,
+                authority: self.authority,
+                user: self.user,
+                system_program: system_program::ID,
+                token_program: spl_token::ID,
+                rent: sysvar::rent::ID,
+                associated_token_program: associated_token::ID,
+            },
+            swap::instruction::QuoteSwap { lamport_amount }
+        )
+    }
+
+    fn migrate_instruction(&self) -> Instruction {
+        Self::instruction(
+            swap::accounts::MigratePool {
+                pool_config_account: self.pool_config_account,
+                authority: self.authority,
+                system_program: system_program::ID,
+            },
+            swap::instruction::MigratePoolInstruction {}
+        )
+    }
+
+    fn set_pause_instruction(&self, authority: Pubkey, paused: bool) -> Instruction {
+        Self::instruction(
+            swap::accounts::SetPause {
+                pool_config_account: self.pool_config_account,
+                token_mint_address: self.mint,
+                authority,
+                pool_roles: None,
+            },
+            swap::instruction::SetPauseInstruction { flags: PAUSE_ALL, paused, reason: 1 }
+        )
+    }
+
+    fn withdraw_token_accounts(&self, master_authority: Pubkey) -> swap::accounts::WithdrawToken {
+        swap::accounts::WithdrawToken {
+            pool_token_account: self.pool_token_account,
+            pool_config_account: self.pool_config_account,
+            token_mint_address: self.mint,
+            program_config: self.program_config,
+            destination_token_account: self.master_token_account,
+            authority: self.authority,
+            master_authority,
+            pool_multisig: None,
+            pool_roles: None,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+        }
+    }
+
+    fn withdraw_native_accounts(&self, master_authority: Pubkey) -> swap::accounts::WithdrawNative {
+        swap::accounts::WithdrawNative {
+            pool_native_account: self.pool_native_account,
+            pool_config_account: self.pool_config_account,
+            token_mint_address: self.mint,
+            program_config: self.program_config,
+            authority: self.authority,
+            master_authority,
+            destination: self.master_authority,
+            pool_multisig: None,
+            pool_roles: None,
+            system_program: system_program::ID,
+        }
+    }
+}
+
+fn assert_custom_error(result: Result<(), BanksClientError>, error: CustomError) {
+    match result {
+        Err(
+            BanksClientError::TransactionError(
+                TransactionError::InstructionError(0, InstructionError::Custom(code)),
+            ),
+        ) => assert_eq!(code, u32::from(error)),
+        other => panic!("expected custom error {}, got {:?}", u32::from(error), other),
+    }
+}
+
+#[tokio::test]
+async fn init_pool() {
+    let (mut pool, keys) = Pool::start().await;
+    pool.process(pool.init_instruction(), &[&keys.authority]).await.unwrap();
+
+    let config = pool.config().await;
+    assert_eq!(config.token_price, TOKEN_PRICE);
+    assert_eq!(config.price_denominator, 1);
+    assert_eq!(config.token_mint_address, pool.mint);
+    assert_eq!(config.pool_token_account, pool.pool_token_account);
+    assert_eq!(config.pool_native_account, pool.pool_native_account);
+    assert_eq!(config.authority, pool.authority);
+    assert_eq!(config.master_authority, pool.master_authority);
+    assert_eq!(config.pause_flags, 0);
+    let program_config: ProgramConfig = pool.account(pool.program_config).await;
+    assert_eq!(program_config.pool_count, 1);
+}
+
+#[tokio::test]
+async fn init_pool_with_rational_price() {
+    let (mut pool, keys) = Pool::start().await;
+    let init = pool.init_instruction_with_price(TOKEN_PRICE, 0);
+    let result = pool.process(init, &[&keys.authority]).await;
+    assert_custom_error(result, CustomError::InvalidPrice);
+    // not even u64::MAX lamports buy a base unit
+    let init = pool.init_instruction_with_price(1, u64::MAX);
+    let result = pool.process(init, &[&keys.authority]).await;
+    assert_custom_error(result, CustomError::InvalidPrice);
+
+    // one base unit per 2 SOL
+    pool.process(pool.init_instruction_with_price(1, 2), &[&keys.authority]).await.unwrap();
+    assert_eq!(pool.config().await.price_denominator, 2);
+    pool.process(pool.add_liquid_instruction(LIQUIDITY), &[&keys.authority]).await.unwrap();
+
+    let lamports = 2 * LAMPORTS_PER_SOL;
+    pool.process(pool.swap_instruction(lamports), &[&keys.authority, &keys.user]).await.unwrap();
+    assert_eq!(pool.token_balance(pool.user_token_account()).await, 1);
+}
+
+#[tokio::test]
+async fn init_pool_with_huge_denominator_and_decimals() {
+    // passes check_token_price, but the price bound no longer fits in a u128
+    let (mut pool, keys) = Pool::start_with_decimals(12).await;
+    let init = pool.init_instruction_with_price(100_000_000, 1_000_000_000_000_000_000);
+    let result = pool.process(init, &[&keys.authority]).await;
+    assert_custom_error(result, CustomError::InvalidPrice);
+}
+
+#[tokio::test]
+async fn swap_at_rational_price() {
+    let (mut pool, keys) = Pool::start().await;
+    // 0.0037 SOL per whole token, 10_000 tokens per 37 SOL
+    let token_price = 10_000 * 10u64.pow(DECIMALS as u32);
+    let init = pool.init_instruction_with_price(token_price, 37);
+    pool.process(init, &[&keys.authority]).await.unwrap();
+    assert_eq!(pool.config().await.price_denominator, 37);
+    pool.process(pool.add_liquid_instruction(LIQUIDITY), &[&keys.authority]).await.unwrap();
+
+    let lamports = (37 * LAMPORTS_PER_SOL) / 10_000;
+    pool.process(pool.swap_instruction(lamports), &[&keys.authority, &keys.user]).await.unwrap();
+    assert_eq!(pool.token_balance(pool.user_token_account()).await, 10u64.pow(DECIMALS as u32));
+}
+
+#[tokio::test]
+async fn add_liquid() {
+    let (mut pool, keys) = Pool::start().await;
+    pool.process(pool.init_instruction(), &[&keys.authority]).await.unwrap();
+
+    let result = pool.process(pool.add_liquid_instruction(MINTED + 1), &[&keys.authority]).await;
+    assert_custom_error(result, CustomError::InsufficientFunds);
+
+    pool.process(pool.add_liquid_instruction(LIQUIDITY), &[&keys.authority]).await.unwrap();
+    assert_eq!(pool.token_balance(pool.pool_token_account).await, LIQUIDITY);
+    assert_eq!(pool.config().await.total_liquidity_added, LIQUIDITY);
+}
+
+#[tokio::test]
+async fn swap_token() {
+    let (mut pool, keys) = Pool::funded().await;
+    let lamports = LAMPORTS_PER_SOL / 2;
+    let tokens_out = TOKEN_PRICE / 2;
+
+    pool.process(pool.swap_instruction(lamports), &[&keys.authority, &keys.user]).await.unwrap();
+    pool.process(pool.swap_instruction(lamports), &[&keys.authority, &keys.user]).await.unwrap();
+    assert_eq!(pool.token_balance(pool.user_token_account()).await, 2 * tokens_out);
+    assert_eq!(pool.token_balance(pool.pool_token_account).await, LIQUIDITY - 2 * tokens_out);
+
+    let config = pool.config().await;
+    assert_eq!(config.swap_count, 2);
+    assert_eq!(config.unique_buyers, 1);
+    assert_eq!(config.total_lamports_in, 2 * lamports);
+    let user_stats: UserStats = pool.account(pool.user_stats()).await;
+    assert_eq!(user_stats.swap_count, 2);
+    assert_eq!(user_stats.tokens_out, 2 * tokens_out);
+
+    let balance = pool.lamports(pool.user).await;
+    let result = pool.process(pool.swap_instruction(balance + 1), &[&keys.authority, &keys.user]).await;
+    assert_custom_error(result, CustomError::InsufficientUserLamports);
+}
+
+#[tokio::test]
+async fn quote_checks_the_swap_preconditions() {
+    let (mut pool, keys) = Pool::funded().await;
+    pool.process(pool.quote_instruction(LAMPORTS_PER_SOL), &[]).await.unwrap();
+
+    // one whole token more than the reserve holds
+    let lamports = (LIQUIDITY / TOKEN_PRICE) * LAMPORTS_PER_SOL + LAMPORTS_PER_SOL / 10;
+    let result = pool.process(pool.quote_instruction(lamports), &[]).await;
+    assert_custom_error(result, CustomError::InsufficientPoolTokens);
+
+    pool.process(pool.set_pause_instruction(keys.authority.pubkey(), true), &[&keys.authority]).await.unwrap();
+    let result = pool.process(pool.quote_instruction(LAMPORTS_PER_SOL), &[]).await;
+    assert_custom_error(result, CustomError::DeactivatePool);
+}
+
+#[tokio::test]
+async fn deactivate_and_reactivate() {
+    let (mut pool, keys) = Pool::funded().await;
+
+    let result = pool.process(pool.set_pause_instruction(keys.user.pubkey(), true), &[&keys.user]).await;
+    assert_custom_error(result, CustomError::InvalidAuthority);
+
+    pool.process(pool.set_pause_instruction(keys.authority.pubkey(), true), &[&keys.authority]).await.unwrap();
+    assert_eq!(pool.config().await.pause_flags, PAUSE_ALL);
+    let result = pool.process(pool.swap_instruction(LAMPORTS_PER_SOL), &[&keys.authority, &keys.user]).await;
+    assert_custom_error(result, CustomError::DeactivatePool);
+    let result = pool.process(pool.add_liquid_instruction(LIQUIDITY), &[&keys.authority]).await;
+    assert_custom_error(result, CustomError::DepositPaused);
+
+    let result = pool.process(pool.set_pause_instruction(keys.user.pubkey(), false), &[&keys.user]).await;
+    assert_custom_error(result, CustomError::InvalidAuthority);
+
+    pool.process(pool.set_pause_instruction(keys.authority.pubkey(), false), &[&keys.authority]).await.unwrap();
+    assert_eq!(pool.config().await.pause_flags, 0);
+    pool.process(pool.swap_instruction(LAMPORTS_PER_SOL), &[&keys.authority, &keys.user]).await.unwrap();
+}
+
+#[tokio::test]
+async fn withdraw_and_drain_token() {
+    let (mut pool, keys) = Pool::funded().await;
+
+    let result = pool.process(
+        Pool::instruction(
+            pool.withdraw_token_accounts(keys.user.pubkey()),
+            swap::instruction::WithdrawTokenInstruction { amount: 1 }
+        ),
+        &[&keys.authority, &keys.user]
+    ).await;
+    assert_custom_error(result, CustomError::WithdrawPermission);
+
+    let result = pool.process(
+        Pool::instruction(
+            pool.withdraw_token_accounts(keys.master_authority.pubkey()),
+            swap::instruction::WithdrawTokenInstruction { amount: LIQUIDITY + 1 }
+        ),
+        &[&keys.authority, &keys.master_authority]
+    ).await;
+    assert_custom_error(result, CustomError::InsufficientPoolTokens);
+
+    let amount = LIQUIDITY / 4;
+    pool.process(
+        Pool::instruction(
+            pool.withdraw_token_accounts(keys.master_authority.pubkey()),
+            swap::instruction::WithdrawTokenInstruction { amount }
+        ),
+        &[&keys.authority, &keys.master_authority]
+    ).await.unwrap();
+    assert_eq!(pool.token_balance(pool.master_token_account).await, amount);
+    assert_eq!(pool.token_balance(pool.pool_token_account).await, LIQUIDITY - amount);
+
+    pool.process(
+        Pool::instruction(
+            pool.withdraw_token_accounts(keys.master_authority.pubkey()),
+            swap::instruction::DrainTokenInstruction {}
+        ),
+        &[&keys.authority, &keys.master_authority]
+    ).await.unwrap();
+    assert_eq!(pool.token_balance(pool.master_token_account).await, LIQUIDITY);
+    assert_eq!(pool.token_balance(pool.pool_token_account).await, 0);
+
+    let result = pool.process(pool.swap_instruction(LAMPORTS_PER_SOL), &[&keys.authority, &keys.user]).await;
+    assert_custom_error(result, CustomError::InsufficientPoolTokens);
+}
+
+#[tokio::test]
+async fn withdraw_and_drain_native() {
+    let (mut pool, keys) = Pool::funded().await;
+    pool.process(pool.swap_instruction(LAMPORTS_PER_SOL), &[&keys.authority, &keys.user]).await.unwrap();
+
+    let result = pool.process(
+        Pool::instruction(
+            pool.withdraw_native_accounts(keys.user.pubkey()),
+            swap::instruction::WithdrawNativeInstruction { amount: 1 }
+        ),
+        &[&keys.authority, &keys.user]
+    ).await;
+    assert_custom_error(result, CustomError::WithdrawPermission);
+
+    let result = pool.process(
+        Pool::instruction(
+            pool.withdraw_native_accounts(keys.master_authority.pubkey()),
+            swap::instruction::WithdrawNativeInstruction { amount: 10 * LAMPORTS_PER_SOL }
+        ),
+        &[&keys.authority, &keys.master_authority]
+    ).await;
+    assert_custom_error(result, CustomError::InsufficientPoolLamports);
+
+    let before = pool.lamports(keys.master_authority.pubkey()).await;
+    pool.process(
+        Pool::instruction(
+            pool.withdraw_native_accounts(keys.master_authority.pubkey()),
+            swap::instruction::WithdrawNativeInstruction { amount: LAMPORTS_PER_SOL / 2 }
+        ),
+        &[&keys.authority, &keys.master_authority]
+    ).await.unwrap();
+    assert_eq!(pool.lamports(keys.master_authority.pubkey()).await, before + LAMPORTS_PER_SOL / 2);
+
+    pool.process(
+        Pool::instruction(
+            pool.withdraw_native_accounts(keys.master_authority.pubkey()),
+            swap::instruction::DrainNativeInstruction {}
+        ),
+        &[&keys.authority, &keys.master_authority]
+    ).await.unwrap();
+    let rent = pool.context.banks_client.get_rent().await.unwrap();
+    assert_eq!(pool.lamports(pool.pool_native_account).await, rent.minimum_balance(0));
+
+    let result = pool.process(
+        Pool::instruction(
+            pool.withdraw_native_accounts(keys.master_authority.pubkey()),
+            swap::instruction::WithdrawNativeInstruction { amount: 1 }
+        ),
+        &[&keys.authority, &keys.master_authority]
+    ).await;
+    assert_custom_error(result, CustomError::NativeVaultRentExempt);
+}
+
+#[tokio::test]
+async fn migrate_pool_from_version_1() {
+    let (mut pool, keys) = Pool::funded().await;
+    // version 1 pools had no denominator stored
+    let mut config = pool.config().await;
+    config.version = 1;
+    config.price_denominator = 0;
+    let mut account = pool.context.banks_client
+        .get_account(pool.pool_config_account).await
+        .unwrap()
+        .unwrap();
+    account.data.clear();
+    config.try_serialize(&mut account.data).unwrap();
+    account.data.resize(PoolConfigAccount::LEN, 0);
+    pool.context.set_account(&pool.pool_config_account, &AccountSharedData::from(account));
+
+    let migrate = pool.migrate_instruction();
+    pool.process(migrate.clone(), &[&keys.authority]).await.unwrap();
+
+    let config = pool.config().await;
+    assert_eq!(config.version, 2);
+    assert_eq!(config.price_denominator, 1);
+    assert_eq!(config.token_price, TOKEN_PRICE);
+
+    let result = pool.process(migrate, &[&keys.authority]).await;
+    assert_custom_error(result, CustomError::PoolAlreadyMigrated);
+}
+
+#[tokio::test]
+async fn migrate_pool_from_legacy_layout() {
+    let (mut pool, keys) = Pool::funded().await;
+    // rewrite the pool in the unversioned 180 byte layout, is_active where pause_flags is now
+    let config = pool.config().await;
+    let mut account = pool.context.banks_client
+        .get_account(pool.pool_config_account).await
+        .unwrap()
+        .unwrap();
+    let mut data = account.data[..8].to_vec();
+    data.extend_from_slice(
+        &[
+            config.pool_config_account_bump,
+            config.pool_token_account_bump,
+            config.pool_native_account_bump,
+        ]
+    );
+    data.extend_from_slice(&config.token_price.to_le_bytes());
+    data.push(1); // is_active
+    for key in [
+        config.token_mint_address,
+        config.pool_token_account,
+        config.pool_native_account,
+        config.master_authority,
+        config.authority,
+    ] {
+        data.extend_from_slice(key.as_ref());
+    }
+    assert_eq!(data.len(), LEGACY_POOL_CONFIG_LEN);
+    account.data = data;
+    pool.context.set_account(&pool.pool_config_account, &AccountSharedData::from(account));
+
+    pool.process(pool.migrate_instruction(), &[&keys.authority]).await.unwrap();
+
+    let account = pool.context.banks_client
+        .get_account(pool.pool_config_account).await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), PoolConfigAccount::LEN);
+    assert!(account.lamports >= Rent::default().minimum_balance(PoolConfigAccount::LEN));
+    let config = pool.config().await;
+    assert_eq!(config.version, 2);
+    assert_eq!(config.pause_flags, 0);
+    assert_eq!(config.creator, pool.authority);
+    assert_eq!(config.authority, pool.authority);
+    assert_eq!(config.master_authority, pool.master_authority);
+    assert_eq!(config.price_denominator, 1);
+    assert_eq!(config.token_price, TOKEN_PRICE);
+
+    // the pool keeps trading from the same PDAs
+    pool.process(pool.swap_instruction(LAMPORTS_PER_SOL / 2), &[&keys.authority, &keys.user]).await.unwrap();
+    assert_eq!(pool.token_balance(pool.user_token_account()).await, TOKEN_PRICE / 2);
+}
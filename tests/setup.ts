@@ -158,3 +158,116 @@ export async function airDrop(
   );
   await connection.confirmTransaction(sig);
 }
+
+// mirrors pool_index_seed in utils.rs, index 0 adds no seed bytes
+export function poolIndexSeed(poolIndex: number) {
+  return poolIndex === 0
+    ? Buffer.alloc(0)
+    : new anchor.BN(poolIndex).toArrayLike(Buffer, "le", 8);
+}
+
+export function findPoolAccounts(
+  programId: anchor.web3.PublicKey,
+  creator: anchor.web3.PublicKey,
+  mint: anchor.web3.PublicKey,
+  poolIndex: number
+) {
+  const indexSeed = poolIndexSeed(poolIndex);
+  const [poolConfigAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("pool_config_account_seed"),
+      creator.toBuffer(),
+      mint.toBuffer(),
+      indexSeed,
+    ],
+    programId
+  );
+  const [poolTokenAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("pool_token_account_seed"),
+      creator.toBuffer(),
+      mint.toBuffer(),
+      indexSeed,
+      poolConfigAccount.toBuffer(),
+    ],
+    programId
+  );
+  const [poolNativeAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("pool_native_account_seed"),
+      creator.toBuffer(),
+      mint.toBuffer(),
+      indexSeed,
+      poolConfigAccount.toBuffer(),
+    ],
+    programId
+  );
+  return { poolConfigAccount, poolTokenAccount, poolNativeAccount };
+}
+
+export function findProgramConfig(programId: anchor.web3.PublicKey) {
+  return anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("program_config_seed")],
+    programId
+  )[0];
+}
+
+export function findPoolRegistryEntry(
+  programId: anchor.web3.PublicKey,
+  index: anchor.BN
+) {
+  return anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("pool_registry_seed"), index.toArrayLike(Buffer, "le", 8)],
+    programId
+  )[0];
+}
+
+// decodes the events a confirmed transaction emitted through program logs
+export async function getEvents(program: Program<Swap>, signature: string) {
+  const connection = program.provider.connection;
+  await connection.confirmTransaction(signature, "confirmed");
+  const tx = await connection.getTransaction(signature, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+  const parser = new anchor.EventParser(
+    program.programId,
+    new anchor.BorshCoder(program.idl)
+  );
+  return Array.from(parser.parseLogs(tx.meta.logMessages));
+}
+
+// mirrors the PAUSE_* bits in constants.rs
+export const PAUSE_SWAP = 1 << 0;
+export const PAUSE_DEPOSIT = 1 << 1;
+export const PAUSE_WITHDRAW = 1 << 2;
+
+// mirrors the ROLE_* bits in constants.rs
+export const ROLE_PRICE_SETTER = 1 << 0;
+export const ROLE_PAUSER = 1 << 1;
+export const ROLE_LIQUIDITY_MANAGER = 1 << 2;
+export const ROLE_TREASURER = 1 << 3;
+
+// update_pool_params takes every field as an option, unset fields stay unchanged
+export function poolParamsArgs(params: {
+  tokenPrice?: anchor.BN;
+  priceDenominator?: anchor.BN;
+  feeBps?: number;
+  outflowWindow?: anchor.BN;
+  tokenOutflowLimit?: anchor.BN;
+  nativeOutflowLimit?: anchor.BN;
+  maxPriceChangeBps?: number;
+  priceUpdateCooldown?: anchor.BN;
+}) {
+  return {
+    tokenPrice: null,
+    priceDenominator: null,
+    feeBps: null,
+    outflowWindow: null,
+    tokenOutflowLimit: null,
+    nativeOutflowLimit: null,
+    maxPriceChangeBps: null,
+    priceUpdateCooldown: null,
+    ...params,
+  };
+}
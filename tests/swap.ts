@@ -1,12 +1,28 @@
 import * as anchor from "@coral-xyz/anchor";
 import {
+  createAssociatedTokenAccount,
   getAccount,
   getAssociatedTokenAddress,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import { formatUnits, parseUnits } from "@ethersproject/units";
 import { assert } from "chai";
-import { airDrop, createToken, mintTo, setup } from "./setup";
+import {
+  airDrop,
+  createToken,
+  findPoolAccounts,
+  findPoolRegistryEntry,
+  findProgramConfig,
+  getEvents,
+  mintTo,
+  PAUSE_DEPOSIT,
+  PAUSE_SWAP,
+  PAUSE_WITHDRAW,
+  poolParamsArgs,
+  ROLE_PAUSER,
+  ROLE_PRICE_SETTER,
+  setup,
+} from "./setup";
 
 describe("swap", async () => {
   const connection = new anchor.web3.Connection(
@@ -16,6 +32,15 @@ describe("swap", async () => {
   const authority = anchor.web3.Keypair.generate();
   const user = anchor.web3.Keypair.generate();
   const masterAuthority = anchor.web3.Keypair.generate();
+  const newAuthority = anchor.web3.Keypair.generate();
+  const treasury = anchor.web3.Keypair.generate();
+  const guardian = anchor.web3.Keypair.generate();
+  const operator = anchor.web3.Keypair.generate();
+  const multisigSigners = [
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+    anchor.web3.Keypair.generate(),
+  ];
 
   const program = await setup(connection, authority);
   let mintAddress: anchor.web3.PublicKey;
@@ -23,6 +48,10 @@ describe("swap", async () => {
   let poolTokenAccount: anchor.web3.PublicKey;
   let poolNativeAccount: anchor.web3.PublicKey;
   let poolConfigAccount: anchor.web3.PublicKey;
+  let poolMultisig: anchor.web3.PublicKey;
+  let poolRoles: anchor.web3.PublicKey;
+  let userStats: anchor.web3.PublicKey;
+  let programConfig: anchor.web3.PublicKey;
   let masterAuthorityTokenAccount: anchor.web3.PublicKey;
   let userTokenAccount: anchor.web3.PublicKey;
   const decimals = 6;
@@ -30,6 +59,18 @@ describe("swap", async () => {
   const addLiquidAmount = 10000;
   const withdrawAmount = 100;
   const swapSolValue = 1;
+  const withdrawalDelay = 2;
+  const creationFee = new anchor.BN(0.01 * anchor.web3.LAMPORTS_PER_SOL);
+
+  const findPendingWithdrawal = (nonce: anchor.BN) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("pending_withdrawal_seed"),
+        poolConfigAccount.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
 
   before(async () => {
     // airdrop 10 SOL for each wallet
@@ -54,7 +95,9 @@ describe("swap", async () => {
       mintAddress,
       10000000000
     );
-    masterAuthorityTokenAccount = await getAssociatedTokenAddress(
+    masterAuthorityTokenAccount = await createAssociatedTokenAccount(
+      connection,
+      authority,
       mintAddress,
       masterAuthority.publicKey
     );
@@ -92,6 +135,27 @@ describe("swap", async () => {
       program.programId
     )[0];
 
+    poolMultisig = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_multisig_seed"), poolConfigAccount.toBuffer()],
+      program.programId
+    )[0];
+
+    programConfig = findProgramConfig(program.programId);
+
+    poolRoles = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_roles_seed"), poolConfigAccount.toBuffer()],
+      program.programId
+    )[0];
+
+    userStats = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("user_stats_seed"),
+        poolConfigAccount.toBuffer(),
+        user.publicKey.toBuffer(),
+      ],
+      program.programId
+    )[0];
+
     console.log({
       poolConfigAccount: poolConfigAccount.toString(),
       poolNativeAccount: poolNativeAccount.toString(),
@@ -99,28 +163,121 @@ describe("swap", async () => {
     });
   });
 
+  it("[Fail case] Init program config without upgrade authority", async () => {
+    const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      anchor.web3.BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .initProgramConfigInstruction(authority.publicKey, treasury.publicKey)
+        .accounts({
+          programConfig: programConfig,
+          program: program.programId,
+          programData: programData,
+          upgradeAuthority: authority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidUpgradeAuthority");
+    }
+    assert.equal(sig, null);
+  });
+
+  it("Init program config", async () => {
+    // the wallet that deployed the program is its upgrade authority
+    const upgradeAuthority = anchor.Wallet.local().payer;
+    const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      anchor.web3.BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+    const sig = await program.methods
+      .initProgramConfigInstruction(authority.publicKey, treasury.publicKey)
+      .accounts({
+        programConfig: programConfig,
+        program: program.programId,
+        programData: programData,
+        upgradeAuthority: upgradeAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([upgradeAuthority])
+      .rpc();
+    const [programConfigUpdated] = await getEvents(program, sig);
+    assert.equal(programConfigUpdated.name, "ProgramConfigUpdated");
+    assert.equal(
+      programConfigUpdated.data.authority.toString(),
+      upgradeAuthority.publicKey.toString()
+    );
+    assert.equal(
+      programConfigUpdated.data.admin.toString(),
+      authority.publicKey.toString()
+    );
+    assert.equal(
+      programConfigUpdated.data.protocolFeeRecipient.toString(),
+      treasury.publicKey.toString()
+    );
+    const programConfigData = await program.account.programConfig.fetch(
+      programConfig
+    );
+    assert.equal(
+      programConfigData.admin.toString(),
+      authority.publicKey.toString()
+    );
+    assert.equal(
+      programConfigData.protocolFeeRecipient.toString(),
+      treasury.publicKey.toString()
+    );
+    assert.equal(programConfigData.paused, false);
+  });
+
   it("Is initialized!", async () => {
     // Add your test here.
     const rawAmount = parseUnits(tokenPrice.toString(), decimals).toNumber();
-    await program.methods
-      .initInstruction(new anchor.BN(rawAmount))
+    const { poolCount } = await program.account.programConfig.fetch(
+      programConfig
+    );
+    const sig = await program.methods
+      .initInstruction(new anchor.BN(rawAmount), new anchor.BN(0), new anchor.BN(1))
       .accounts({
         poolConfigAccount: poolConfigAccount,
         poolNativeAccount: poolNativeAccount,
         poolTokenAccount: poolTokenAccount,
         tokenMintAddress: mintAddress,
+        poolRegistryEntry: findPoolRegistryEntry(program.programId, poolCount),
         authority: authority.publicKey,
         masterAuthority: masterAuthority.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
         rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        programConfig: programConfig,
+        protocolFeeRecipient: treasury.publicKey,
       })
       .rpc();
+    const [poolInitialized] = await getEvents(program, sig);
+    assert.equal(poolInitialized.name, "PoolInitialized");
+    assert.equal(
+      poolInitialized.data.poolConfigAccount.toString(),
+      poolConfigAccount.toString()
+    );
+    assert.equal(
+      poolInitialized.data.tokenMintAddress.toString(),
+      mintAddress.toString()
+    );
+    assert.equal(
+      poolInitialized.data.masterAuthority.toString(),
+      masterAuthority.publicKey.toString()
+    );
+    assert.equal(Number(poolInitialized.data.tokenPrice), rawAmount);
+    assert.equal(Number(poolInitialized.data.priceDenominator), 1);
+    assert.equal(Number(poolInitialized.data.poolIndex), 0);
 
     const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
       poolConfigAccount
     );
     assert.equal(Number(poolConfigAccountData.tokenPrice), rawAmount);
+    assert.equal(Number(poolConfigAccountData.priceDenominator), 1);
     assert.equal(
       poolConfigAccountData.tokenMintAddress.toString(),
       mintAddress.toString()
@@ -153,6 +310,8 @@ describe("swap", async () => {
           depositor: authority.publicKey, // reuse authority as a depositor to liquid pool
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
+          poolRoles: null,
+          programConfig: programConfig,
         })
         .rpc();
     } catch (error) {
@@ -169,7 +328,7 @@ describe("swap", async () => {
       addLiquidAmount.toString(),
       decimals
     ).toNumber();
-    await program.methods
+    const sig = await program.methods
       .addLiquidInstruction(new anchor.BN(rawAmount))
       .accounts({
         poolConfigAccount: poolConfigAccount,
@@ -180,14 +339,28 @@ describe("swap", async () => {
         depositor: authority.publicKey, // reuse authority as a depositor to liquid pool
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
+        poolRoles: null,
+        programConfig: programConfig,
       })
       .rpc();
+    const [liquidityAdded] = await getEvents(program, sig);
+    assert.equal(liquidityAdded.name, "LiquidityAdded");
+    assert.equal(
+      liquidityAdded.data.depositor.toString(),
+      authority.publicKey.toString()
+    );
+    assert.equal(Number(liquidityAdded.data.amount), rawAmount);
+    assert.equal(Number(liquidityAdded.data.poolTokenBalance), rawAmount);
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(Number(poolConfigAccountData.totalLiquidityAdded), rawAmount);
     const info = await getAccount(connection, poolTokenAccount);
     assert.equal(Number(info.amount), rawAmount);
   });
 
   it("[Success] Swap Token", async () => {
-    await program.methods
+    const sig = await program.methods
       .swapToken(new anchor.BN(swapSolValue * anchor.web3.LAMPORTS_PER_SOL))
       .accounts({
         poolConfigAccount: poolConfigAccount,
@@ -199,9 +372,22 @@ describe("swap", async () => {
         user: user.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
+        userStats: userStats,
+        programConfig: programConfig,
       })
       .signers([user])
       .rpc();
+    const [tokensSwapped] = await getEvents(program, sig);
+    assert.equal(tokensSwapped.name, "TokensSwapped");
+    assert.equal(
+      tokensSwapped.data.user.toString(),
+      user.publicKey.toString()
+    );
+    assert.equal(
+      Number(tokensSwapped.data.lamportsIn),
+      swapSolValue * anchor.web3.LAMPORTS_PER_SOL
+    );
+    assert.equal(Number(tokensSwapped.data.feeLamports), 0);
     const userTokenBalance = await getAccount(connection, userTokenAccount);
     const rawTokenPrice = parseUnits(
       tokenPrice.toString(),
@@ -213,39 +399,47 @@ describe("swap", async () => {
     assert.equal(Number(userTokenBalance.amount), tokenReceive);
   });
 
-  it("deactivate pool config", async () => {
-    await program.methods
-      .updateConfigInstruction(false)
+  it("Pause swaps", async () => {
+    const sig = await program.methods
+      .setPauseInstruction(PAUSE_SWAP, true, 1)
       .accounts({
         poolConfigAccount: poolConfigAccount,
         tokenMintAddress: mintAddress,
         authority: authority.publicKey,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        systemProgram: anchor.web3.SystemProgram.programId,
+        poolRoles: null,
       })
       .rpc();
+    const [pauseUpdated] = await getEvents(program, sig);
+    assert.equal(pauseUpdated.name, "PauseUpdated");
+    assert.equal(
+      pauseUpdated.data.authority.toString(),
+      authority.publicKey.toString()
+    );
+    assert.equal(pauseUpdated.data.pauseFlags, PAUSE_SWAP);
+    assert.equal(pauseUpdated.data.pauseReason, 1);
     const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
       poolConfigAccount
     );
-    assert.equal(poolConfigAccountData.isActive, false);
+    assert.equal(poolConfigAccountData.pauseFlags, PAUSE_SWAP);
+    assert.equal(poolConfigAccountData.pauseReason, 1);
   });
 
-  it("[Fail case] Without permission deactivate pool config", async () => {
+  it("[Fail case] Without permission pause deposits", async () => {
     let sig: string | null;
     try {
       sig = await program.methods
-        .updateConfigInstruction(false)
+        .setPauseInstruction(PAUSE_DEPOSIT, true, 1)
         .accounts({
           poolConfigAccount: poolConfigAccount,
           tokenMintAddress: mintAddress,
           authority: user.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
-          systemProgram: anchor.web3.SystemProgram.programId,
+          poolRoles: null,
         })
         .signers([user])
         .rpc();
     } catch (error) {
-      assert.equal(error.error.errorCode.code, "ConstraintSeeds");
+      assert.equal(error.error.errorCode.code, "InvalidAuthority");
+      assert.equal(error.error.errorCode.number, 6003);
     }
     assert.equal(sig, null);
   });
@@ -265,6 +459,8 @@ describe("swap", async () => {
           user: user.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
+          userStats: userStats,
+          programConfig: programConfig,
         })
         .signers([user])
         .rpc();
@@ -276,41 +472,129 @@ describe("swap", async () => {
     assert.equal(sig, null);
   });
 
-  it("[Fail case] Without permission activate pool config", async () => {
+  it("[Fail case] Without permission resume swaps", async () => {
     let sig: string | null;
     try {
       sig = await program.methods
-        .updateConfigInstruction(true)
+        .setPauseInstruction(PAUSE_SWAP, false, 0)
         .accounts({
           poolConfigAccount: poolConfigAccount,
           tokenMintAddress: mintAddress,
           authority: user.publicKey,
+          poolRoles: null,
+        })
+        .signers([user])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidAuthority");
+      assert.equal(error.error.errorCode.number, 6003);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("[Fail case] Add liquid while deposits are paused", async () => {
+    await program.methods
+      .setPauseInstruction(PAUSE_DEPOSIT, true, 2)
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        poolRoles: null,
+      })
+      .rpc();
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .addLiquidInstruction(new anchor.BN(1))
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          poolTokenAccount: poolTokenAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          depositorTokenAccount: associatedAccount,
+          depositor: authority.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
+          poolRoles: null,
+          programConfig: programConfig,
+        })
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "DepositPaused");
+      assert.equal(error.error.errorCode.number, 6023);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("[Fail case] Invalid pause flags", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .setPauseInstruction(1 << 3, true, 0)
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          poolRoles: null,
         })
-        .signers([user])
         .rpc();
     } catch (error) {
-      assert.equal(error.error.errorCode.code, "ConstraintSeeds");
+      assert.equal(error.error.errorCode.code, "InvalidPauseFlags");
+      assert.equal(error.error.errorCode.number, 6025);
     }
     assert.equal(sig, null);
   });
 
-  it("Activate pool config", async () => {
+  it("Resume swaps and deposits", async () => {
     await program.methods
-      .updateConfigInstruction(true)
+      .setPauseInstruction(PAUSE_SWAP | PAUSE_DEPOSIT, false, 0)
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        poolRoles: null,
+      })
+      .rpc();
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(poolConfigAccountData.pauseFlags, 0);
+    assert.equal(poolConfigAccountData.pauseReason, 0);
+  });
+
+  it("Quote swap through simulation", async () => {
+    const lamports = swapSolValue * anchor.web3.LAMPORTS_PER_SOL;
+    const quote = await program.methods
+      .quoteSwap(new anchor.BN(lamports))
       .accounts({
         poolConfigAccount: poolConfigAccount,
+        poolTokenAccount: poolTokenAccount,
+        poolNativeAccount: poolNativeAccount,
         tokenMintAddress: mintAddress,
         authority: authority.publicKey,
+        userTokenAccount: userTokenAccount,
+        user: user.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
+        userStats: userStats,
+        programConfig: programConfig,
       })
-      .rpc();
+      .view();
+    const rawTokenPrice = parseUnits(
+      tokenPrice.toString(),
+      decimals
+    ).toNumber();
+    assert.equal(Number(quote.lamportsIn), lamports);
+    assert.equal(Number(quote.feeLamports), 0);
+    assert.equal(Number(quote.tokensOut), rawTokenPrice * swapSolValue);
+    assert.equal(Number(quote.tokenPrice), rawTokenPrice);
+    assert.equal(Number(quote.effectivePrice), rawTokenPrice);
+
+    // nothing moved
     const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
       poolConfigAccount
     );
-    assert.equal(poolConfigAccountData.isActive, true);
+    assert.equal(Number(poolConfigAccountData.swapCount), 1);
   });
 
   it("Swap Token", async () => {
@@ -328,6 +612,8 @@ describe("swap", async () => {
         user: user.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
+        userStats: userStats,
+        programConfig: programConfig,
       })
       .signers([user])
       .rpc();
@@ -343,6 +629,31 @@ describe("swap", async () => {
       Number(userTokenBalance.amount) - Number(beforeBalance),
       tokenReceive
     );
+
+    // second swap from the same user, counted once as a buyer
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(Number(poolConfigAccountData.swapCount), 2);
+    assert.equal(Number(poolConfigAccountData.uniqueBuyers), 1);
+    assert.equal(
+      Number(poolConfigAccountData.totalLamportsIn),
+      2 * swapSolValue * anchor.web3.LAMPORTS_PER_SOL
+    );
+    assert.equal(
+      Number(poolConfigAccountData.totalTokensOut),
+      2 * tokenReceive
+    );
+    const userStatsData = await program.account.userStats.fetch(userStats);
+    assert.equal(Number(userStatsData.swapCount), 2);
+    assert.equal(
+      userStatsData.user.toString(),
+      user.publicKey.toString()
+    );
+    assert.equal(
+      Number(userStatsData.lastSwapAt),
+      Number(poolConfigAccountData.lastSwapAt)
+    );
   });
 
   it("[fail case] Swap Token insufficient funds", async () => {
@@ -361,13 +672,15 @@ describe("swap", async () => {
           user: user.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
+          userStats: userStats,
+          programConfig: programConfig,
         })
         .signers([user])
         .rpc();
     } catch (error) {
-      assert.equal(error.error.errorCode.code, "InsufficientFunds");
-      assert.equal(error.error.errorCode.number, 6000);
-      assert.equal(error.error.errorMessage, "user insufficient funds");
+      assert.equal(error.error.errorCode.code, "InsufficientUserLamports");
+      assert.equal(error.error.errorCode.number, 6034);
+      assert.equal(error.error.errorMessage, "user insufficient lamports");
     }
     assert.equal(sig, null);
   });
@@ -388,11 +701,14 @@ describe("swap", async () => {
           poolConfigAccount: poolConfigAccount,
           poolTokenAccount: poolTokenAccount,
           tokenMintAddress: mintAddress,
-          masterAuthorityTokenAccount: userTokenAccount,
+          destinationTokenAccount: userTokenAccount,
           masterAuthority: user.publicKey,
           authority: authority.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
+          poolMultisig: null,
+          poolRoles: null,
+          programConfig: programConfig,
         })
         .signers([user])
         .rpc();
@@ -418,18 +734,21 @@ describe("swap", async () => {
           poolConfigAccount: poolConfigAccount,
           poolTokenAccount: poolTokenAccount,
           tokenMintAddress: mintAddress,
-          masterAuthorityTokenAccount: masterAuthorityTokenAccount,
+          destinationTokenAccount: masterAuthorityTokenAccount,
           masterAuthority: masterAuthority.publicKey,
           authority: authority.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
+          poolMultisig: null,
+          poolRoles: null,
+          programConfig: programConfig,
         })
         .signers([masterAuthority])
         .rpc();
     } catch (error) {
-      assert.equal(error.error.errorCode.code, "InsufficientFunds");
-      assert.equal(error.error.errorCode.number, 6000);
-      assert.equal(error.error.errorMessage, "user insufficient funds");
+      assert.equal(error.error.errorCode.code, "InsufficientPoolTokens");
+      assert.equal(error.error.errorCode.number, 6035);
+      assert.equal(error.error.errorMessage, "pool insufficient tokens");
     }
     assert.equal(sig, null);
   });
@@ -443,20 +762,30 @@ describe("swap", async () => {
 
     const poolBalanceBefore = (await getAccount(connection, poolTokenAccount))
       .amount;
-    await program.methods
+    const sig = await program.methods
       .withdrawTokenInstruction(new anchor.BN(rawAmount))
       .accounts({
         poolConfigAccount: poolConfigAccount,
         poolTokenAccount: poolTokenAccount,
         tokenMintAddress: mintAddress,
-        masterAuthorityTokenAccount: masterAuthorityTokenAccount,
+        destinationTokenAccount: masterAuthorityTokenAccount,
         masterAuthority: masterAuthority.publicKey,
         authority: authority.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
+        poolMultisig: null,
+        poolRoles: null,
+        programConfig: programConfig,
       })
       .signers([masterAuthority])
       .rpc();
+    const [tokenWithdrawn] = await getEvents(program, sig);
+    assert.equal(tokenWithdrawn.name, "TokenWithdrawn");
+    assert.equal(
+      tokenWithdrawn.data.destination.toString(),
+      masterAuthorityTokenAccount.toString()
+    );
+    assert.equal(Number(tokenWithdrawn.data.amount), rawAmount);
     const poolBalanceAfter = (await getAccount(connection, poolTokenAccount))
       .amount;
     const masterAuthorityTokenBalanceAfter = (
@@ -469,26 +798,173 @@ describe("swap", async () => {
     assert.equal(Number(masterAuthorityTokenBalanceAfter), rawAmount);
   });
 
+  it("Set multisig for withdrawals", async () => {
+    const sig = await program.methods
+      .setMultisigInstruction(
+        multisigSigners.map((signer) => signer.publicKey),
+        2
+      )
+      .accounts({
+        poolMultisig: poolMultisig,
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        masterAuthority: masterAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([masterAuthority])
+      .rpc();
+    const [multisigUpdated] = await getEvents(program, sig);
+    assert.equal(multisigUpdated.name, "MultisigUpdated");
+    assert.equal(
+      multisigUpdated.data.multisig.toString(),
+      poolMultisig.toString()
+    );
+    assert.equal(multisigUpdated.data.signers.length, 3);
+    assert.equal(multisigUpdated.data.threshold, 2);
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.multisig.toString(),
+      poolMultisig.toString()
+    );
+    const poolMultisigData = await program.account.poolMultisig.fetch(
+      poolMultisig
+    );
+    assert.equal(poolMultisigData.threshold, 2);
+    assert.equal(poolMultisigData.signers.length, 3);
+  });
+
+  it("[Fail case] Withdraw token below multisig threshold", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .withdrawTokenInstruction(new anchor.BN(1))
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          poolTokenAccount: poolTokenAccount,
+          tokenMintAddress: mintAddress,
+          destinationTokenAccount: masterAuthorityTokenAccount,
+          masterAuthority: masterAuthority.publicKey,
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          poolMultisig: poolMultisig,
+          poolRoles: null,
+          programConfig: programConfig,
+        })
+        .remainingAccounts([
+          {
+            pubkey: multisigSigners[0].publicKey,
+            isSigner: true,
+            isWritable: false,
+          },
+        ])
+        .signers([masterAuthority, multisigSigners[0]])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "MultisigThresholdNotMet");
+      assert.equal(error.error.errorCode.number, 6008);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("Withdraw token with multisig approvals", async () => {
+    const approvers = multisigSigners.slice(0, 2);
+    const poolBalanceBefore = (await getAccount(connection, poolTokenAccount))
+      .amount;
+    await program.methods
+      .withdrawTokenInstruction(new anchor.BN(1))
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        poolTokenAccount: poolTokenAccount,
+        tokenMintAddress: mintAddress,
+        destinationTokenAccount: masterAuthorityTokenAccount,
+        masterAuthority: masterAuthority.publicKey,
+        authority: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        poolMultisig: poolMultisig,
+        poolRoles: null,
+        programConfig: programConfig,
+      })
+      .remainingAccounts(
+        approvers.map((signer) => ({
+          pubkey: signer.publicKey,
+          isSigner: true,
+          isWritable: false,
+        }))
+      )
+      .signers([masterAuthority, ...approvers])
+      .rpc();
+    const poolBalanceAfter = (await getAccount(connection, poolTokenAccount))
+      .amount;
+    assert.equal(Number(poolBalanceBefore) - Number(poolBalanceAfter), 1);
+  });
+
+  it("Disable multisig with multisig approvals", async () => {
+    const approvers = multisigSigners.slice(1, 3);
+    const sig = await program.methods
+      .setMultisigInstruction([], 0)
+      .accounts({
+        poolMultisig: poolMultisig,
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        masterAuthority: masterAuthority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .remainingAccounts(
+        approvers.map((signer) => ({
+          pubkey: signer.publicKey,
+          isSigner: true,
+          isWritable: false,
+        }))
+      )
+      .signers([masterAuthority, ...approvers])
+      .rpc();
+    const [multisigUpdated] = await getEvents(program, sig);
+    assert.equal(multisigUpdated.name, "MultisigUpdated");
+    assert.equal(
+      multisigUpdated.data.multisig.toString(),
+      anchor.web3.PublicKey.default.toString()
+    );
+    assert.equal(multisigUpdated.data.signers.length, 0);
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.multisig.toString(),
+      anchor.web3.PublicKey.default.toString()
+    );
+  });
+
   it("Drain Token", async () => {
     const poolBalanceBefore = (await getAccount(connection, poolTokenAccount))
       .amount;
     const masterAuthorityTokenBalanceBefore = (
       await getAccount(connection, masterAuthorityTokenAccount)
     ).amount;
-    await program.methods
+    const sig = await program.methods
       .drainTokenInstruction()
       .accounts({
         poolConfigAccount: poolConfigAccount,
         poolTokenAccount: poolTokenAccount,
         tokenMintAddress: mintAddress,
-        masterAuthorityTokenAccount: masterAuthorityTokenAccount,
+        destinationTokenAccount: masterAuthorityTokenAccount,
         masterAuthority: masterAuthority.publicKey,
         authority: authority.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
+        poolMultisig: null,
+        poolRoles: null,
+        programConfig: programConfig,
       })
       .signers([masterAuthority])
       .rpc();
+    const [poolDrained] = await getEvents(program, sig);
+    assert.equal(poolDrained.name, "PoolDrained");
+    assert.deepEqual(poolDrained.data.kind, { token: {} });
+    assert.equal(Number(poolDrained.data.amount), Number(poolBalanceBefore));
     const poolBalanceAfter = (await getAccount(connection, poolTokenAccount))
       .amount;
     const masterAuthorityTokenBalanceAfter = (
@@ -517,8 +993,11 @@ describe("swap", async () => {
           tokenMintAddress: mintAddress,
           masterAuthority: user.publicKey,
           authority: authority.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
+          poolMultisig: null,
+          destination: masterAuthority.publicKey,
+          poolRoles: null,
+          programConfig: programConfig,
         })
         .signers([user])
         .rpc();
@@ -543,15 +1022,18 @@ describe("swap", async () => {
           tokenMintAddress: mintAddress,
           masterAuthority: masterAuthority.publicKey,
           authority: authority.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: anchor.web3.SystemProgram.programId,
+          poolMultisig: null,
+          destination: masterAuthority.publicKey,
+          poolRoles: null,
+          programConfig: programConfig,
         })
         .signers([masterAuthority])
         .rpc();
     } catch (error) {
-      assert.equal(error.error.errorCode.code, "InsufficientFunds");
-      assert.equal(error.error.errorCode.number, 6000);
-      assert.equal(error.error.errorMessage, "user insufficient funds");
+      assert.equal(error.error.errorCode.code, "InsufficientPoolLamports");
+      assert.equal(error.error.errorCode.number, 6036);
+      assert.equal(error.error.errorMessage, "pool insufficient lamports");
     }
     assert.equal(sig, null);
   });
@@ -560,7 +1042,7 @@ describe("swap", async () => {
     const masterAuthorityBalanceBefore = await connection.getBalance(
       masterAuthority.publicKey
     );
-    await program.methods
+    const sig = await program.methods
       .withdrawNativeInstruction(
         new anchor.BN(0.5 * anchor.web3.LAMPORTS_PER_SOL)
       )
@@ -570,11 +1052,24 @@ describe("swap", async () => {
         tokenMintAddress: mintAddress,
         masterAuthority: masterAuthority.publicKey,
         authority: authority.publicKey,
-        tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
+        poolMultisig: null,
+        destination: masterAuthority.publicKey,
+        poolRoles: null,
+        programConfig: programConfig,
       })
       .signers([masterAuthority])
       .rpc();
+    const [nativeWithdrawn] = await getEvents(program, sig);
+    assert.equal(nativeWithdrawn.name, "NativeWithdrawn");
+    assert.equal(
+      nativeWithdrawn.data.destination.toString(),
+      masterAuthority.publicKey.toString()
+    );
+    assert.equal(
+      Number(nativeWithdrawn.data.lamports),
+      0.5 * anchor.web3.LAMPORTS_PER_SOL
+    );
     const masterAuthorityBalanceAfter = await connection.getBalance(
       masterAuthority.publicKey
     );
@@ -584,21 +1079,1494 @@ describe("swap", async () => {
     );
   });
 
-  it("Drain Native", async () => {
-    await program.methods
-      .drainNativeInstruction()
+  //////////// TIMELOCKED WITHDRAWAL
+
+  it("Set withdrawal delay", async () => {
+    const sig = await program.methods
+      .setWithdrawalDelayInstruction(new anchor.BN(withdrawalDelay))
       .accounts({
         poolConfigAccount: poolConfigAccount,
-        poolNativeAccount: poolNativeAccount,
         tokenMintAddress: mintAddress,
-        masterAuthority: masterAuthority.publicKey,
         authority: authority.publicKey,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        systemProgram: anchor.web3.SystemProgram.programId,
+        masterAuthority: masterAuthority.publicKey,
       })
       .signers([masterAuthority])
       .rpc();
-    const poolBalance = await connection.getBalance(poolNativeAccount);
-    assert.equal(poolBalance, 0);
+    const [withdrawalDelayUpdated] = await getEvents(program, sig);
+    assert.equal(withdrawalDelayUpdated.name, "WithdrawalDelayUpdated");
+    assert.equal(
+      Number(withdrawalDelayUpdated.data.withdrawalDelay),
+      withdrawalDelay
+    );
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      Number(poolConfigAccountData.withdrawalDelay),
+      withdrawalDelay
+    );
+  });
+
+  it("[Fail case] Withdraw native while timelocked", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .withdrawNativeInstruction(new anchor.BN(1))
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          poolNativeAccount: poolNativeAccount,
+          tokenMintAddress: mintAddress,
+          masterAuthority: masterAuthority.publicKey,
+          authority: authority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          poolMultisig: null,
+          destination: masterAuthority.publicKey,
+          poolRoles: null,
+          programConfig: programConfig,
+        })
+        .signers([masterAuthority])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "WithdrawalTimelocked");
+      assert.equal(error.error.errorCode.number, 6009);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("[Fail case] Queue a withdrawal without the treasurer role", async () => {
+    const nonce = (
+      await program.account.poolConfigAccount.fetch(poolConfigAccount)
+    ).withdrawalNonce;
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .queueWithdrawalInstruction({ native: {} }, new anchor.BN(1))
+        .accounts({
+          pendingWithdrawal: findPendingWithdrawal(nonce),
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: user.publicKey,
+          masterAuthority: masterAuthority.publicKey,
+          destination: masterAuthority.publicKey,
+          poolMultisig: null,
+          poolRoles: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          programConfig: programConfig,
+        })
+        .signers([user, masterAuthority])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidAuthority");
+      assert.equal(error.error.errorCode.number, 6003);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("Queue, cancel and execute native withdrawals", async () => {
+    const lamports = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+    const queue = async () => {
+      const nonce = (
+        await program.account.poolConfigAccount.fetch(poolConfigAccount)
+      ).withdrawalNonce;
+      const pendingWithdrawal = findPendingWithdrawal(nonce);
+      const sig = await program.methods
+        .queueWithdrawalInstruction({ native: {} }, new anchor.BN(lamports))
+        .accounts({
+          pendingWithdrawal: pendingWithdrawal,
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          masterAuthority: masterAuthority.publicKey,
+          destination: masterAuthority.publicKey,
+          poolMultisig: null,
+          poolRoles: null,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          programConfig: programConfig,
+        })
+        .signers([masterAuthority])
+        .rpc();
+      const [withdrawalQueued] = await getEvents(program, sig);
+      assert.equal(withdrawalQueued.name, "WithdrawalQueued");
+      assert.equal(
+        withdrawalQueued.data.pendingWithdrawal.toString(),
+        pendingWithdrawal.toString()
+      );
+      assert.equal(
+        withdrawalQueued.data.nonce.toString(),
+        nonce.toString()
+      );
+      assert.equal(Number(withdrawalQueued.data.amount), lamports);
+      assert.equal(
+        withdrawalQueued.data.destination.toString(),
+        masterAuthority.publicKey.toString()
+      );
+      return pendingWithdrawal;
+    };
+    const execute = (pendingWithdrawal: anchor.web3.PublicKey) =>
+      program.methods
+        .executeWithdrawalInstruction()
+        .accounts({
+          pendingWithdrawal: pendingWithdrawal,
+          poolTokenAccount: poolTokenAccount,
+          poolNativeAccount: poolNativeAccount,
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          destination: masterAuthority.publicKey,
+          payer: masterAuthority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          programConfig: programConfig,
+        })
+        .rpc();
+
+    const executed = await queue();
+    let sig: string | null;
+    try {
+      sig = await execute(executed);
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "WithdrawalLocked");
+      assert.equal(error.error.errorCode.number, 6010);
+    }
+    assert.equal(sig, null);
+
+    const cancelled = await queue();
+    const cancelSig = await program.methods
+      .cancelWithdrawalInstruction()
+      .accounts({
+        pendingWithdrawal: cancelled,
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        payer: masterAuthority.publicKey,
+      })
+      .rpc();
+    const [withdrawalCancelled] = await getEvents(program, cancelSig);
+    assert.equal(withdrawalCancelled.name, "WithdrawalCancelled");
+    assert.equal(
+      withdrawalCancelled.data.pendingWithdrawal.toString(),
+      cancelled.toString()
+    );
+    assert.equal(await connection.getAccountInfo(cancelled), null);
+
+    await new Promise((resolve) =>
+      setTimeout(resolve, (withdrawalDelay + 1) * 1000)
+    );
+    const poolBalanceBefore = await connection.getBalance(poolNativeAccount);
+    const executeSig = await execute(executed);
+    const poolBalanceAfter = await connection.getBalance(poolNativeAccount);
+    const [withdrawalExecuted, nativeWithdrawn] = await getEvents(
+      program,
+      executeSig
+    );
+    assert.equal(withdrawalExecuted.name, "WithdrawalExecuted");
+    assert.equal(
+      withdrawalExecuted.data.pendingWithdrawal.toString(),
+      executed.toString()
+    );
+    assert.equal(nativeWithdrawn.name, "NativeWithdrawn");
+    assert.equal(Number(nativeWithdrawn.data.lamports), lamports);
+    assert.equal(poolBalanceBefore - poolBalanceAfter, lamports);
+    assert.equal(await connection.getAccountInfo(executed), null);
+  });
+
+  it("[Fail case] Execute a withdrawal to a destination no longer allowed", async () => {
+    const nonce = (
+      await program.account.poolConfigAccount.fetch(poolConfigAccount)
+    ).withdrawalNonce;
+    const pendingWithdrawal = findPendingWithdrawal(nonce);
+    await program.methods
+      .queueWithdrawalInstruction({ native: {} }, new anchor.BN(1))
+      .accounts({
+        pendingWithdrawal: pendingWithdrawal,
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        masterAuthority: masterAuthority.publicKey,
+        destination: masterAuthority.publicKey,
+        poolMultisig: null,
+        poolRoles: null,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        programConfig: programConfig,
+      })
+      .signers([masterAuthority])
+      .rpc();
+    const setDestinations = (destinations: anchor.web3.PublicKey[]) =>
+      program.methods
+        .setWithdrawDestinationsInstruction(destinations)
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          masterAuthority: masterAuthority.publicKey,
+        })
+        .signers([masterAuthority])
+        .rpc();
+    await setDestinations([treasury.publicKey]);
+
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .executeWithdrawalInstruction()
+        .accounts({
+          pendingWithdrawal: pendingWithdrawal,
+          poolTokenAccount: poolTokenAccount,
+          poolNativeAccount: poolNativeAccount,
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          destination: masterAuthority.publicKey,
+          payer: masterAuthority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          programConfig: programConfig,
+        })
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidDestination");
+      assert.equal(error.error.errorCode.number, 6012);
+    }
+    assert.equal(sig, null);
+
+    await program.methods
+      .cancelWithdrawalInstruction()
+      .accounts({
+        pendingWithdrawal: pendingWithdrawal,
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        payer: masterAuthority.publicKey,
+      })
+      .rpc();
+    await setDestinations([]);
+  });
+
+  it("Remove withdrawal delay", async () => {
+    await program.methods
+      .setWithdrawalDelayInstruction(new anchor.BN(0))
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        masterAuthority: masterAuthority.publicKey,
+      })
+      .signers([masterAuthority])
+      .rpc();
+  });
+
+  //////////// WITHDRAW DESTINATIONS
+
+  it("Restrict withdraw destinations", async () => {
+    const sig = await program.methods
+      .setWithdrawDestinationsInstruction([treasury.publicKey])
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        masterAuthority: masterAuthority.publicKey,
+      })
+      .signers([masterAuthority])
+      .rpc();
+    const [destinationsUpdated] = await getEvents(program, sig);
+    assert.equal(destinationsUpdated.name, "WithdrawDestinationsUpdated");
+    assert.deepEqual(
+      destinationsUpdated.data.destinations.map((key) => key.toString()),
+      [treasury.publicKey.toString()]
+    );
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.withdrawDestinations[0].toString(),
+      treasury.publicKey.toString()
+    );
+  });
+
+  it("[Fail case] Withdraw native outside destination allowlist", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .withdrawNativeInstruction(
+          new anchor.BN(0.01 * anchor.web3.LAMPORTS_PER_SOL)
+        )
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          poolNativeAccount: poolNativeAccount,
+          tokenMintAddress: mintAddress,
+          masterAuthority: masterAuthority.publicKey,
+          authority: authority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          poolMultisig: null,
+          destination: masterAuthority.publicKey,
+          poolRoles: null,
+          programConfig: programConfig,
+        })
+        .signers([masterAuthority])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidDestination");
+      assert.equal(error.error.errorCode.number, 6012);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("Withdraw native to treasury", async () => {
+    const lamports = 0.01 * anchor.web3.LAMPORTS_PER_SOL;
+    await program.methods
+      .withdrawNativeInstruction(new anchor.BN(lamports))
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        poolNativeAccount: poolNativeAccount,
+        tokenMintAddress: mintAddress,
+        masterAuthority: masterAuthority.publicKey,
+        authority: authority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        poolMultisig: null,
+        destination: treasury.publicKey,
+        poolRoles: null,
+        programConfig: programConfig,
+      })
+      .signers([masterAuthority])
+      .rpc();
+    assert.equal(await connection.getBalance(treasury.publicKey), lamports);
+  });
+
+  it("Lift withdraw destination restriction", async () => {
+    const sig = await program.methods
+      .setWithdrawDestinationsInstruction([])
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        masterAuthority: masterAuthority.publicKey,
+      })
+      .signers([masterAuthority])
+      .rpc();
+    const [destinationsUpdated] = await getEvents(program, sig);
+    assert.equal(destinationsUpdated.name, "WithdrawDestinationsUpdated");
+    assert.equal(destinationsUpdated.data.destinations.length, 0);
+  });
+
+  //////////// OUTFLOW LIMITS
+
+  it("Native withdrawals respect the outflow limit", async () => {
+    const limit = 0.02 * anchor.web3.LAMPORTS_PER_SOL;
+    const setOutflowLimits = (window: number, nativeLimit: number) =>
+      program.methods
+        .updatePoolParamsInstruction(
+          poolParamsArgs({
+            outflowWindow: new anchor.BN(window),
+            nativeOutflowLimit: new anchor.BN(nativeLimit),
+          })
+        )
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          masterAuthority: masterAuthority.publicKey,
+          poolRoles: null,
+        })
+        .signers([masterAuthority])
+        .rpc();
+    const withdrawNative = (lamports: number) =>
+      program.methods
+        .withdrawNativeInstruction(new anchor.BN(lamports))
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          poolNativeAccount: poolNativeAccount,
+          tokenMintAddress: mintAddress,
+          masterAuthority: masterAuthority.publicKey,
+          authority: authority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          poolMultisig: null,
+          destination: masterAuthority.publicKey,
+          poolRoles: null,
+          programConfig: programConfig,
+        })
+        .signers([masterAuthority])
+        .rpc();
+
+    await setOutflowLimits(3600, limit);
+    await withdrawNative(0.015 * anchor.web3.LAMPORTS_PER_SOL);
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      Number(poolConfigAccountData.nativeOutflowInWindow),
+      0.015 * anchor.web3.LAMPORTS_PER_SOL
+    );
+
+    let sig: string | null;
+    try {
+      sig = await withdrawNative(0.01 * anchor.web3.LAMPORTS_PER_SOL);
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "OutflowLimitExceeded");
+      assert.equal(error.error.errorCode.number, 6014);
+    }
+    assert.equal(sig, null);
+
+    // re-sending the same limits keeps what already left in the window
+    await setOutflowLimits(3600, limit);
+    sig = null;
+    try {
+      sig = await withdrawNative(0.01 * anchor.web3.LAMPORTS_PER_SOL);
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "OutflowLimitExceeded");
+    }
+    assert.equal(sig, null);
+
+    await setOutflowLimits(0, 0);
+  });
+
+  it("Drain Native", async () => {
+    await program.methods
+      .drainNativeInstruction()
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        poolNativeAccount: poolNativeAccount,
+        tokenMintAddress: mintAddress,
+        masterAuthority: masterAuthority.publicKey,
+        authority: authority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        poolMultisig: null,
+        destination: masterAuthority.publicKey,
+        poolRoles: null,
+        programConfig: programConfig,
+      })
+      .signers([masterAuthority])
+      .rpc();
+    const poolBalance = await connection.getBalance(poolNativeAccount);
+    const rentExemptMinimum =
+      await connection.getMinimumBalanceForRentExemption(0);
+    assert.equal(poolBalance, rentExemptMinimum);
+  });
+
+  it("[Fail case] Withdraw native below rent exemption", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .withdrawNativeInstruction(new anchor.BN(1))
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          poolNativeAccount: poolNativeAccount,
+          tokenMintAddress: mintAddress,
+          masterAuthority: masterAuthority.publicKey,
+          authority: authority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          poolMultisig: null,
+          destination: masterAuthority.publicKey,
+          poolRoles: null,
+          programConfig: programConfig,
+        })
+        .signers([masterAuthority])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "NativeVaultRentExempt");
+      assert.equal(error.error.errorCode.number, 6005);
+      assert.equal(
+        error.error.errorMessage,
+        "native vault must stay rent exempt"
+      );
+    }
+    assert.equal(sig, null);
+  });
+
+  //////////// AUTHORITY TRANSFER
+
+  it("[Fail case] Accept authority without proposal", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .acceptAuthorityInstruction()
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          newAuthority: newAuthority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidPendingAuthority");
+      assert.equal(error.error.errorCode.number, 6006);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("Transfer authority", async () => {
+    const proposeSig = await program.methods
+      .proposeAuthorityInstruction(newAuthority.publicKey)
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+      })
+      .rpc();
+    const [authorityProposed] = await getEvents(program, proposeSig);
+    assert.equal(authorityProposed.name, "AuthorityProposed");
+    assert.equal(authorityProposed.data.master, false);
+    assert.equal(
+      authorityProposed.data.proposed.toString(),
+      newAuthority.publicKey.toString()
+    );
+    const acceptSig = await program.methods
+      .acceptAuthorityInstruction()
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        newAuthority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc();
+    const [authorityTransferred] = await getEvents(program, acceptSig);
+    assert.equal(authorityTransferred.name, "AuthorityTransferred");
+    assert.equal(authorityTransferred.data.master, false);
+    assert.equal(
+      authorityTransferred.data.previous.toString(),
+      authority.publicKey.toString()
+    );
+    assert.equal(
+      authorityTransferred.data.new.toString(),
+      newAuthority.publicKey.toString()
+    );
+    let poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.authority.toString(),
+      newAuthority.publicKey.toString()
+    );
+    assert.equal(
+      poolConfigAccountData.pendingAuthority.toString(),
+      anchor.web3.PublicKey.default.toString()
+    );
+    // pool PDAs stay derived from the creator
+    assert.equal(
+      poolConfigAccountData.creator.toString(),
+      authority.publicKey.toString()
+    );
+
+    // hand the pool back to the original authority
+    await program.methods
+      .proposeAuthorityInstruction(authority.publicKey)
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptAuthorityInstruction()
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        newAuthority: authority.publicKey,
+      })
+      .rpc();
+    poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.authority.toString(),
+      authority.publicKey.toString()
+    );
+  });
+
+  it("[Fail case] Propose master authority without permission", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .proposeMasterAuthorityInstruction(user.publicKey)
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          masterAuthority: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "WithdrawPermission");
+      assert.equal(error.error.errorCode.number, 6004);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("Transfer master authority", async () => {
+    const proposeSig = await program.methods
+      .proposeMasterAuthorityInstruction(newAuthority.publicKey)
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        masterAuthority: masterAuthority.publicKey,
+      })
+      .signers([masterAuthority])
+      .rpc();
+    const [authorityProposed] = await getEvents(program, proposeSig);
+    assert.equal(authorityProposed.name, "AuthorityProposed");
+    assert.equal(authorityProposed.data.master, true);
+    assert.equal(
+      authorityProposed.data.current.toString(),
+      masterAuthority.publicKey.toString()
+    );
+    const acceptSig = await program.methods
+      .acceptMasterAuthorityInstruction()
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        newMasterAuthority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc();
+    const [authorityTransferred] = await getEvents(program, acceptSig);
+    assert.equal(authorityTransferred.name, "AuthorityTransferred");
+    assert.equal(authorityTransferred.data.master, true);
+    assert.equal(
+      authorityTransferred.data.new.toString(),
+      newAuthority.publicKey.toString()
+    );
+    let poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.masterAuthority.toString(),
+      newAuthority.publicKey.toString()
+    );
+
+    await program.methods
+      .proposeMasterAuthorityInstruction(masterAuthority.publicKey)
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        masterAuthority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptMasterAuthorityInstruction()
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        newMasterAuthority: masterAuthority.publicKey,
+      })
+      .signers([masterAuthority])
+      .rpc();
+    poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.masterAuthority.toString(),
+      masterAuthority.publicKey.toString()
+    );
+  });
+
+  //////////// POOL PARAMS
+
+  it("Update token price and fee", async () => {
+    const before = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    const newPrice = before.tokenPrice.muln(2);
+    const sig = await program.methods
+      .updatePoolParamsInstruction(
+        poolParamsArgs({ tokenPrice: newPrice, feeBps: 30 })
+      )
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        masterAuthority: null,
+        poolRoles: null,
+      })
+      .rpc();
+    const [configUpdated] = await getEvents(program, sig);
+    assert.equal(configUpdated.name, "ConfigUpdated");
+    assert.equal(
+      configUpdated.data.before.tokenPrice.toString(),
+      before.tokenPrice.toString()
+    );
+    assert.equal(
+      configUpdated.data.after.tokenPrice.toString(),
+      newPrice.toString()
+    );
+    assert.equal(configUpdated.data.after.feeBps, 30);
+    assert.equal(configUpdated.data.after.pauseFlags, before.pauseFlags);
+    let poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.tokenPrice.toString(),
+      newPrice.toString()
+    );
+    assert.equal(poolConfigAccountData.feeBps, 30);
+    assert.equal(poolConfigAccountData.pauseFlags, before.pauseFlags);
+
+    await program.methods
+      .updatePoolParamsInstruction(
+        poolParamsArgs({ tokenPrice: before.tokenPrice, feeBps: 0 })
+      )
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        masterAuthority: null,
+        poolRoles: null,
+      })
+      .rpc();
+    poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.tokenPrice.toString(),
+      before.tokenPrice.toString()
+    );
+  });
+
+  it("[Fail case] Update pool params with invalid fee", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .updatePoolParamsInstruction(poolParamsArgs({ feeBps: 10001 }))
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          masterAuthority: null,
+          poolRoles: null,
+        })
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidFee");
+      assert.equal(error.error.errorCode.number, 6017);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("[Fail case] Update outflow limits without master authority", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .updatePoolParamsInstruction(
+          poolParamsArgs({ outflowWindow: new anchor.BN(0) })
+        )
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          masterAuthority: null,
+          poolRoles: null,
+        })
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "MasterSignatureRequired");
+      assert.equal(error.error.errorCode.number, 6018);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("[Fail case] Update outflow window above a year", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .updatePoolParamsInstruction(
+          poolParamsArgs({ outflowWindow: new anchor.BN(366 * 24 * 60 * 60) })
+        )
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          masterAuthority: masterAuthority.publicKey,
+          poolRoles: null,
+        })
+        .signers([masterAuthority])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidOutflowLimit");
+      assert.equal(error.error.errorCode.number, 6015);
+    }
+    assert.equal(sig, null);
+  });
+
+  //////////// PRICE GUARDRAILS
+
+  it("Price moves above the guardrail need master authority", async () => {
+    const updatePrice = (
+      params: ReturnType<typeof poolParamsArgs>,
+      withMaster: boolean
+    ) =>
+      program.methods
+        .updatePoolParamsInstruction(params)
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          masterAuthority: withMaster ? masterAuthority.publicKey : null,
+          poolRoles: null,
+        })
+        .signers(withMaster ? [masterAuthority] : [])
+        .rpc();
+    const { tokenPrice } = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+
+    await updatePrice(poolParamsArgs({ maxPriceChangeBps: 1000 }), true);
+    // +5% is inside the 10% guardrail
+    await updatePrice(
+      poolParamsArgs({ tokenPrice: tokenPrice.muln(105).divn(100) }),
+      false
+    );
+
+    let sig: string | null;
+    try {
+      sig = await updatePrice(
+        poolParamsArgs({ tokenPrice: tokenPrice.muln(2) }),
+        false
+      );
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "PriceChangeTooLarge");
+      assert.equal(error.error.errorCode.number, 6021);
+    }
+    assert.equal(sig, null);
+
+    await updatePrice(poolParamsArgs({ tokenPrice: tokenPrice }), true);
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.tokenPrice.toString(),
+      tokenPrice.toString()
+    );
+
+    await updatePrice(
+      poolParamsArgs({ priceUpdateCooldown: new anchor.BN(3600) }),
+      true
+    );
+    sig = null;
+    try {
+      sig = await updatePrice(poolParamsArgs({ tokenPrice: tokenPrice }), true);
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "PriceUpdateCooldown");
+      assert.equal(error.error.errorCode.number, 6020);
+    }
+    assert.equal(sig, null);
+
+    await updatePrice(
+      poolParamsArgs({
+        maxPriceChangeBps: 0,
+        priceUpdateCooldown: new anchor.BN(0),
+      }),
+      true
+    );
+  });
+
+  it("[Fail case] Price guardrails out of range", async () => {
+    for (const params of [
+      poolParamsArgs({ maxPriceChangeBps: 10001 }),
+      poolParamsArgs({ priceUpdateCooldown: new anchor.BN(-1) }),
+      poolParamsArgs({
+        priceUpdateCooldown: new anchor.BN(31 * 24 * 60 * 60),
+      }),
+    ]) {
+      let sig: string | null = null;
+      try {
+        sig = await program.methods
+          .updatePoolParamsInstruction(params)
+          .accounts({
+            poolConfigAccount: poolConfigAccount,
+            tokenMintAddress: mintAddress,
+            authority: authority.publicKey,
+            masterAuthority: masterAuthority.publicKey,
+            poolRoles: null,
+          })
+          .signers([masterAuthority])
+          .rpc();
+      } catch (error) {
+        assert.equal(error.error.errorCode.code, "InvalidPriceGuardrail");
+        assert.equal(error.error.errorCode.number, 6022);
+      }
+      assert.equal(sig, null);
+    }
+  });
+
+  it("Set guardian", async () => {
+    const sig = await program.methods
+      .setGuardianInstruction(guardian.publicKey)
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+      })
+      .rpc();
+    const [guardianUpdated] = await getEvents(program, sig);
+    assert.equal(guardianUpdated.name, "GuardianUpdated");
+    assert.equal(
+      guardianUpdated.data.guardian.toString(),
+      guardian.publicKey.toString()
+    );
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.guardian.toString(),
+      guardian.publicKey.toString()
+    );
+  });
+
+  it("[Fail case] User pause as guardian", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .guardianPauseInstruction(PAUSE_WITHDRAW, 3)
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          guardian: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidGuardian");
+      assert.equal(error.error.errorCode.number, 6026);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("Guardian pauses withdrawals, authority resumes", async () => {
+    const pauseSig = await program.methods
+      .guardianPauseInstruction(PAUSE_WITHDRAW, 3)
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        guardian: guardian.publicKey,
+      })
+      .signers([guardian])
+      .rpc();
+    const [pauseUpdated] = await getEvents(program, pauseSig);
+    assert.equal(pauseUpdated.name, "PauseUpdated");
+    assert.equal(
+      pauseUpdated.data.authority.toString(),
+      guardian.publicKey.toString()
+    );
+    assert.equal(pauseUpdated.data.pauseFlags, PAUSE_WITHDRAW);
+    assert.equal(pauseUpdated.data.pauseReason, 3);
+    let poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(poolConfigAccountData.pauseFlags, PAUSE_WITHDRAW);
+    assert.equal(poolConfigAccountData.pauseReason, 3);
+
+    // the guardian has no say over resuming
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .setPauseInstruction(PAUSE_WITHDRAW, false, 0)
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: guardian.publicKey,
+          poolRoles: null,
+        })
+        .signers([guardian])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidAuthority");
+    }
+    assert.equal(sig, null);
+
+    await program.methods
+      .setPauseInstruction(PAUSE_WITHDRAW, false, 0)
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        poolRoles: null,
+      })
+      .rpc();
+    poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(poolConfigAccountData.pauseFlags, 0);
+  });
+
+  it("Grant price setter role", async () => {
+    const sig = await program.methods
+      .grantRoleInstruction(operator.publicKey, ROLE_PRICE_SETTER)
+      .accounts({
+        poolRoles: poolRoles,
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    const [roleUpdated] = await getEvents(program, sig);
+    assert.equal(roleUpdated.name, "RoleUpdated");
+    assert.equal(
+      roleUpdated.data.member.toString(),
+      operator.publicKey.toString()
+    );
+    assert.equal(roleUpdated.data.roles, ROLE_PRICE_SETTER);
+    const poolRolesData = await program.account.poolRoles.fetch(poolRoles);
+    assert.equal(poolRolesData.members.length, 1);
+    assert.equal(
+      poolRolesData.members[0].member.toString(),
+      operator.publicKey.toString()
+    );
+    assert.equal(poolRolesData.members[0].roles, ROLE_PRICE_SETTER);
+  });
+
+  it("Price setter updates the fee but can not pause", async () => {
+    await program.methods
+      .updatePoolParamsInstruction(poolParamsArgs({ feeBps: 10 }))
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: operator.publicKey,
+        masterAuthority: null,
+        poolRoles: poolRoles,
+      })
+      .signers([operator])
+      .rpc();
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(poolConfigAccountData.feeBps, 10);
+
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .setPauseInstruction(PAUSE_SWAP, true, 0)
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: operator.publicKey,
+          poolRoles: poolRoles,
+        })
+        .signers([operator])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidAuthority");
+    }
+    assert.equal(sig, null);
+  });
+
+  it("[Fail case] Grant invalid role", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .grantRoleInstruction(operator.publicKey, 1 << 4)
+        .accounts({
+          poolRoles: poolRoles,
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidRole");
+      assert.equal(error.error.errorCode.number, 6027);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("Revoke price setter role", async () => {
+    const revokeSig = await program.methods
+      .revokeRoleInstruction(operator.publicKey, ROLE_PRICE_SETTER | ROLE_PAUSER)
+      .accounts({
+        poolRoles: poolRoles,
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+      })
+      .rpc();
+    const [roleUpdated] = await getEvents(program, revokeSig);
+    assert.equal(roleUpdated.name, "RoleUpdated");
+    assert.equal(
+      roleUpdated.data.member.toString(),
+      operator.publicKey.toString()
+    );
+    assert.equal(roleUpdated.data.roles, 0);
+    const poolRolesData = await program.account.poolRoles.fetch(poolRoles);
+    assert.equal(poolRolesData.members.length, 0);
+
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .updatePoolParamsInstruction(poolParamsArgs({ feeBps: 0 }))
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: operator.publicKey,
+          masterAuthority: null,
+          poolRoles: poolRoles,
+        })
+        .signers([operator])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidAuthority");
+      assert.equal(error.error.errorCode.number, 6003);
+    }
+    assert.equal(sig, null);
+
+    await program.methods
+      .updatePoolParamsInstruction(poolParamsArgs({ feeBps: 0 }))
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        masterAuthority: null,
+        poolRoles: null,
+      })
+      .rpc();
+  });
+
+  it("[Fail case] Migrate a pool already on the current version", async () => {
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(poolConfigAccountData.version, 2);
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .migratePoolInstruction()
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          authority: authority.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "PoolAlreadyMigrated");
+      assert.equal(error.error.errorCode.number, 6029);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("Set pool creation fee", async () => {
+    const sig = await program.methods
+      .updateProgramConfigInstruction(null, null, creationFee)
+      .accounts({
+        programConfig: programConfig,
+        admin: authority.publicKey,
+      })
+      .rpc();
+    const [programConfigUpdated] = await getEvents(program, sig);
+    assert.equal(programConfigUpdated.name, "ProgramConfigUpdated");
+    assert.equal(
+      programConfigUpdated.data.admin.toString(),
+      authority.publicKey.toString()
+    );
+    assert.equal(
+      programConfigUpdated.data.creationFee.toString(),
+      creationFee.toString()
+    );
+    const programConfigData = await program.account.programConfig.fetch(
+      programConfig
+    );
+    assert.equal(
+      programConfigData.creationFee.toString(),
+      creationFee.toString()
+    );
+  });
+
+  it("[Fail case] Init pool for a mint with too many decimals", async () => {
+    const wideMint = await createToken(connection, authority, 18);
+    const widePool = findPoolAccounts(
+      program.programId,
+      authority.publicKey,
+      wideMint,
+      0
+    );
+    const { poolCount } = await program.account.programConfig.fetch(
+      programConfig
+    );
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .initInstruction(new anchor.BN(1), new anchor.BN(0), new anchor.BN(1))
+        .accounts({
+          poolConfigAccount: widePool.poolConfigAccount,
+          poolNativeAccount: widePool.poolNativeAccount,
+          poolTokenAccount: widePool.poolTokenAccount,
+          tokenMintAddress: wideMint,
+          poolRegistryEntry: findPoolRegistryEntry(program.programId, poolCount),
+          authority: authority.publicKey,
+          masterAuthority: masterAuthority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          programConfig: programConfig,
+          protocolFeeRecipient: treasury.publicKey,
+        })
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidMintDecimals");
+      assert.equal(error.error.errorCode.number, 6033);
+    }
+    assert.equal(sig, null);
+  });
+
+  it("Init a second pool of the same mint with another index", async () => {
+    const secondPool = findPoolAccounts(
+      program.programId,
+      authority.publicKey,
+      mintAddress,
+      1
+    );
+    assert.notEqual(
+      secondPool.poolConfigAccount.toString(),
+      poolConfigAccount.toString()
+    );
+    const rawAmount = parseUnits(
+      (tokenPrice * 2).toString(),
+      decimals
+    ).toNumber();
+    const { poolCount } = await program.account.programConfig.fetch(
+      programConfig
+    );
+    const treasuryBalanceBefore = await connection.getBalance(
+      treasury.publicKey
+    );
+    await program.methods
+      .initInstruction(new anchor.BN(rawAmount), new anchor.BN(1), new anchor.BN(1))
+      .accounts({
+        poolConfigAccount: secondPool.poolConfigAccount,
+        poolNativeAccount: secondPool.poolNativeAccount,
+        poolTokenAccount: secondPool.poolTokenAccount,
+        tokenMintAddress: mintAddress,
+        poolRegistryEntry: findPoolRegistryEntry(program.programId, poolCount),
+        authority: authority.publicKey,
+        masterAuthority: masterAuthority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        programConfig: programConfig,
+        protocolFeeRecipient: treasury.publicKey,
+      })
+      .rpc();
+    const secondPoolData = await program.account.poolConfigAccount.fetch(
+      secondPool.poolConfigAccount
+    );
+    assert.equal(Number(secondPoolData.poolIndex), 1);
+    assert.equal(secondPoolData.isVerified, false);
+    assert.equal(
+      (await connection.getBalance(treasury.publicKey)) - treasuryBalanceBefore,
+      creationFee.toNumber()
+    );
+    assert.equal(Number(secondPoolData.tokenPrice), rawAmount);
+    assert.equal(
+      secondPoolData.poolTokenAccount.toString(),
+      secondPool.poolTokenAccount.toString()
+    );
+
+    // the first pool keeps its own price
+    const firstPoolData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(Number(firstPoolData.poolIndex), 0);
+    assert.notEqual(
+      firstPoolData.tokenPrice.toString(),
+      secondPoolData.tokenPrice.toString()
+    );
+  });
+
+  it("Registry lists every created pool", async () => {
+    const { poolCount } = await program.account.programConfig.fetch(
+      programConfig
+    );
+    const entries = await Promise.all(
+      Array.from({ length: poolCount.toNumber() }, (_, index) =>
+        program.account.poolRegistryEntry.fetch(
+          findPoolRegistryEntry(program.programId, new anchor.BN(index))
+        )
+      )
+    );
+    const pools = entries.map((entry) => entry.poolConfigAccount.toString());
+    assert.include(pools, poolConfigAccount.toString());
+    assert.include(
+      pools,
+      findPoolAccounts(
+        program.programId,
+        authority.publicKey,
+        mintAddress,
+        1
+      ).poolConfigAccount.toString()
+    );
+  });
+
+  it("Global pause halts swaps until the admin resumes", async () => {
+    const pauseSig = await program.methods
+      .setGlobalPauseInstruction(true)
+      .accounts({
+        programConfig: programConfig,
+        admin: authority.publicKey,
+      })
+      .rpc();
+    const [globalPauseUpdated] = await getEvents(program, pauseSig);
+    assert.equal(globalPauseUpdated.name, "GlobalPauseUpdated");
+    assert.equal(globalPauseUpdated.data.paused, true);
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .swapToken(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL / 100))
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          poolTokenAccount: poolTokenAccount,
+          poolNativeAccount: poolNativeAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          userTokenAccount: userTokenAccount,
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          userStats: userStats,
+          programConfig: programConfig,
+        })
+        .signers([user])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "ProgramPaused");
+      assert.equal(error.error.errorCode.number, 6030);
+    }
+    assert.equal(sig, null);
+
+    sig = null;
+    try {
+      sig = await program.methods
+        .setGlobalPauseInstruction(false)
+        .accounts({
+          programConfig: programConfig,
+          admin: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidProgramAdmin");
+    }
+    assert.equal(sig, null);
+
+    await program.methods
+      .setGlobalPauseInstruction(false)
+      .accounts({
+        programConfig: programConfig,
+        admin: authority.publicKey,
+      })
+      .rpc();
+    const programConfigData = await program.account.programConfig.fetch(
+      programConfig
+    );
+    assert.equal(programConfigData.paused, false);
+  });
+
+  it("Program admin verifies a pool", async () => {
+    let sig: string | null;
+    try {
+      sig = await program.methods
+        .verifyPoolInstruction(true)
+        .accounts({
+          programConfig: programConfig,
+          poolConfigAccount: poolConfigAccount,
+          admin: user.publicKey,
+        })
+        .signers([user])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidProgramAdmin");
+    }
+    assert.equal(sig, null);
+
+    const verifySig = await program.methods
+      .verifyPoolInstruction(true)
+      .accounts({
+        programConfig: programConfig,
+        poolConfigAccount: poolConfigAccount,
+        admin: authority.publicKey,
+      })
+      .rpc();
+    const [poolVerified] = await getEvents(program, verifySig);
+    assert.equal(poolVerified.name, "PoolVerified");
+    assert.equal(
+      poolVerified.data.poolConfigAccount.toString(),
+      poolConfigAccount.toString()
+    );
+    assert.equal(poolVerified.data.verified, true);
+    const poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(poolConfigAccountData.isVerified, true);
+  });
+
+  it("Repricing a verified pool clears the verification", async () => {
+    const before = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(before.isVerified, true);
+    const sig = await program.methods
+      .updatePoolParamsInstruction(
+        poolParamsArgs({ tokenPrice: before.tokenPrice.muln(2) })
+      )
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        masterAuthority: null,
+        poolRoles: null,
+      })
+      .rpc();
+    const [configUpdated] = await getEvents(program, sig);
+    assert.equal(configUpdated.name, "ConfigUpdated");
+    assert.equal(configUpdated.data.before.isVerified, true);
+    assert.equal(configUpdated.data.after.isVerified, false);
+    let poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(poolConfigAccountData.isVerified, false);
+
+    await program.methods
+      .updatePoolParamsInstruction(
+        poolParamsArgs({ tokenPrice: before.tokenPrice })
+      )
+      .accounts({
+        poolConfigAccount: poolConfigAccount,
+        tokenMintAddress: mintAddress,
+        authority: authority.publicKey,
+        masterAuthority: null,
+        poolRoles: null,
+      })
+      .rpc();
+    poolConfigAccountData = await program.account.poolConfigAccount.fetch(
+      poolConfigAccount
+    );
+    assert.equal(
+      poolConfigAccountData.tokenPrice.toString(),
+      before.tokenPrice.toString()
+    );
+  });
+
+  it("[Fail case] Update price above the init bound", async () => {
+    let sig: string | null;
+    try {
+      // more than MAX_TOKENS_PER_SOL whole tokens per SOL
+      sig = await program.methods
+        .updatePoolParamsInstruction(
+          poolParamsArgs({
+            tokenPrice: new anchor.BN("1000000001").mul(
+              new anchor.BN(10).pow(new anchor.BN(decimals))
+            ),
+            priceDenominator: new anchor.BN(1),
+          })
+        )
+        .accounts({
+          poolConfigAccount: poolConfigAccount,
+          tokenMintAddress: mintAddress,
+          authority: authority.publicKey,
+          masterAuthority: masterAuthority.publicKey,
+          poolRoles: null,
+        })
+        .signers([masterAuthority])
+        .rpc();
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidPrice");
+      assert.equal(error.error.errorCode.number, 6016);
+    }
+    assert.equal(sig, null);
   });
 });